@@ -0,0 +1 @@
+ALTER TABLE articles ADD COLUMN deleted_at TIMESTAMP;
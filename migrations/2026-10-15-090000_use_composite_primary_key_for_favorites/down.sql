@@ -0,0 +1,3 @@
+ALTER TABLE favorites DROP CONSTRAINT favorites_pkey;
+ALTER TABLE favorites ADD CONSTRAINT favorites_article_id_user_id_key UNIQUE (article_id, user_id);
+ALTER TABLE favorites ADD COLUMN id UUID PRIMARY KEY DEFAULT uuid_generate_v4();
@@ -0,0 +1,6 @@
+-- (user_id, article_id) identifies a favorite, so it becomes the primary key and also
+-- replaces the UNIQUE(article_id, user_id) constraint that prevented double-favoriting.
+ALTER TABLE favorites DROP CONSTRAINT favorites_pkey;
+ALTER TABLE favorites DROP COLUMN id;
+ALTER TABLE favorites DROP CONSTRAINT favorites_article_id_user_id_key;
+ALTER TABLE favorites ADD PRIMARY KEY (user_id, article_id);
@@ -0,0 +1,2 @@
+-- NOTE: fails if duplicate usernames already exist; rename them before migrating.
+ALTER TABLE users ADD CONSTRAINT users_username_key UNIQUE (username);
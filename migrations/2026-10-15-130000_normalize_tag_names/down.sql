@@ -0,0 +1 @@
+-- NOTE: the original spelling of normalized tags is not kept, so there is nothing to undo.
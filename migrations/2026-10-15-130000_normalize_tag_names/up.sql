@@ -0,0 +1,9 @@
+-- NOTE: tag names are stored trimmed and lowercased from now on, so existing rows are normalized the
+-- same way. When an article ends up with the same tag twice ("Rust" and "rust"), its oldest row is kept.
+DELETE FROM tags a
+  USING tags b
+  WHERE a.article_id = b.article_id
+    AND lower(trim(a.name)) = lower(trim(b.name))
+    AND (a.created_at, a.id) > (b.created_at, b.id);
+
+UPDATE tags SET name = lower(trim(name)) WHERE name <> lower(trim(name));
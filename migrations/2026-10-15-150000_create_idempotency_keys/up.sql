@@ -0,0 +1,7 @@
+CREATE TABLE idempotency_keys (
+  user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+  key TEXT NOT NULL,
+  response JSONB,
+  created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  PRIMARY KEY (user_id, key)
+);
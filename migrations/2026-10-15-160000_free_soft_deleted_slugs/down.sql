@@ -0,0 +1 @@
+-- NOTE: the original slugs would clash with the articles which took them over, so they stay suffixed.
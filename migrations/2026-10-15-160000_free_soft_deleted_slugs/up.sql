@@ -0,0 +1,5 @@
+-- NOTE: articles soft-deleted before this kept their slug, see `Article::soft_delete`.
+UPDATE articles
+  SET slug = slug || '-deleted-' || id
+  WHERE deleted_at IS NOT NULL
+    AND slug NOT LIKE ('%-deleted-' || id);
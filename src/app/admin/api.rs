@@ -0,0 +1,90 @@
+use super::request::ReadOnlyRequest;
+use super::response::ReadOnlyResponse;
+use crate::constants::header;
+use crate::error::AppError;
+use crate::middleware::error::ErrorResponse;
+use crate::middleware::state::AppState;
+use crate::utils::api::ApiResponse;
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_json::json;
+use std::sync::atomic::Ordering;
+
+#[utoipa::path(
+    put,
+    path = "/api/admin/read-only",
+    tag = "admin",
+    request_body = ReadOnlyRequest,
+    params(("X-Admin-Secret" = String, Header, description = "Value of `ADMIN_SECRET`")),
+    responses(
+        (status = 200, description = "Read-only mode after the toggle", body = ReadOnlyResponse),
+        (status = 401, description = "Missing or wrong admin secret", body = crate::middleware::error::ErrorResponse),
+        (status = 404, description = "Admin endpoints are disabled", body = crate::middleware::error::ErrorResponse),
+    ),
+)]
+pub async fn read_only(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Json<ReadOnlyRequest>,
+) -> ApiResponse {
+    check_admin_secret(&req, state.config.admin_secret.as_deref())?;
+    state.read_only.store(form.read_only, Ordering::SeqCst);
+    info!("Read-only mode set to {}", form.read_only);
+    Ok(HttpResponse::Ok().json(ReadOnlyResponse {
+        read_only: form.read_only,
+    }))
+}
+
+fn check_admin_secret(req: &HttpRequest, admin_secret: Option<&str>) -> Result<(), AppError> {
+    let admin_secret =
+        admin_secret.ok_or_else(|| AppError::NotFound(json!(ErrorResponse::from("not found"))))?;
+    let given = req
+        .headers()
+        .get(header::X_ADMIN_SECRET)
+        .map(|value| value.as_bytes())
+        .unwrap_or_default();
+    if constant_time_eq(given, admin_secret.as_bytes()) {
+        Ok(())
+    } else {
+        Err(AppError::Unauthorized(json!(ErrorResponse::from(
+            "invalid admin secret"
+        ))))
+    }
+}
+
+// NOTE: compares every byte whatever the first mismatch, so timing doesn't leak the secret.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn admin_secret_must_match() {
+        let req = TestRequest::default()
+            .insert_header((header::X_ADMIN_SECRET, "s3cret"))
+            .to_http_request();
+        assert!(check_admin_secret(&req, Some("s3cret")).is_ok());
+        assert!(matches!(
+            check_admin_secret(&req, Some("other")),
+            Err(AppError::Unauthorized(_))
+        ));
+        assert!(matches!(
+            check_admin_secret(&TestRequest::default().to_http_request(), Some("s3cret")),
+            Err(AppError::Unauthorized(_))
+        ));
+    }
+
+    #[test]
+    fn admin_endpoints_disabled_without_secret() {
+        let req = TestRequest::default()
+            .insert_header((header::X_ADMIN_SECRET, ""))
+            .to_http_request();
+        assert!(matches!(
+            check_admin_secret(&req, None),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod api;
+pub mod request;
+pub mod response;
@@ -1,124 +1,476 @@
 use super::{
-    model::{Article, DeleteArticle},
+    model::Article,
     request,
-    response::{MultipleArticlesResponse, SingleArticleResponse},
+    response::{
+        ArticleDraftResponse, BulkDeleteArticlesResponse, MultipleArticlesResponse,
+        SingleArticleResponse,
+    },
     service,
 };
+use crate::app::idempotency::model::IdempotencyKey;
+use crate::config::Config;
+use crate::constants::{self, header};
+use crate::error::AppError;
 use crate::middleware::auth;
+use crate::middleware::error::ErrorResponse;
 use crate::middleware::state::AppState;
 use crate::utils::api::ApiResponse;
+use crate::utils::db;
+use crate::utils::etag;
+use crate::utils::pagination::{self, Pagination};
+use actix_web::http::header::{ETag, LOCATION};
 use actix_web::{web, HttpRequest, HttpResponse};
-use serde::Deserialize;
+use diesel::pg::PgConnection;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use utoipa::IntoParams;
+use uuid::Uuid;
 
 type ArticleTitleSlug = String;
+type UsernameSlug = String;
+type TagName = String;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ArticlesListQueryParameter {
     tag: Option<String>,
     author: Option<String>,
     favorited: Option<String>,
-    limit: Option<i64>,
-    offset: Option<i64>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/articles",
+    tag = "article",
+    params(ArticlesListQueryParameter, pagination::PaginationQuery),
+    responses(
+        (status = 200, description = "List of articles", body = MultipleArticlesResponse),
+        (status = 400, description = "Invalid pagination", body = crate::middleware::error::ErrorResponse),
+    ),
+)]
 pub async fn index(
     state: web::Data<AppState>,
+    req: HttpRequest,
     params: web::Query<ArticlesListQueryParameter>,
+    pagination: Pagination,
 ) -> ApiResponse {
-    let conn = state.get_conn()?;
-    let offset = std::cmp::min(params.offset.to_owned().unwrap_or(0), 100);
-    let limit = params.limit.unwrap_or(20);
+    let conn = state.read_conn()?;
+    let Pagination { limit, offset } = pagination;
 
-    let (articles_list, articles_count) = service::fetch_articles_list(
-        &conn,
-        service::FetchArticlesList {
-            tag: params.tag.clone(),
-            author: params.author.clone(),
-            favorited: params.favorited.clone(),
-            offset,
-            limit,
-        },
-    )?;
+    let current_user = auth::get_current_user(&req).ok();
+
+    let (articles_list, articles_count) = match (&params.favorited, &params.tag, &params.author) {
+        (Some(username), None, None) => state.timed(&req, "article.list_favorited", || {
+            service::list_favorited_by(&conn, username, current_user.as_ref(), limit, offset)
+        })?,
+        _ => state.timed(&req, "article.list", || {
+            service::list(
+                &conn,
+                service::FetchArticlesList {
+                    tag: params.tag.clone(),
+                    author: params.author.clone(),
+                    favorited: params.favorited.clone(),
+                    offset,
+                    limit,
+                },
+                current_user.as_ref(),
+            )
+        })?,
+    };
 
     let res = MultipleArticlesResponse::from((articles_list, articles_count));
-    Ok(HttpResponse::Ok().json(res))
+    Ok(HttpResponse::Ok()
+        .insert_header((header::X_TOTAL_COUNT, articles_count.to_string()))
+        .insert_header((header::X_LIMIT, limit.to_string()))
+        .insert_header((header::X_OFFSET, offset.to_string()))
+        .json(res))
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct FeedQueryParameter {
-    limit: Option<i64>,
-    offset: Option<i64>,
+    /// `X-Next-Cursor` of the previous page. Returns only older articles and ignores `offset`.
+    before: Option<String>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/articles/feed",
+    tag = "article",
+    params(FeedQueryParameter, pagination::PaginationQuery),
+    responses(
+        (status = 200, description = "Articles by followed authors", body = MultipleArticlesResponse),
+        (status = 400, description = "Invalid pagination or cursor", body = crate::middleware::error::ErrorResponse),
+        (status = 401, description = "Unauthorized", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
 pub async fn feed(
     state: web::Data<AppState>,
     req: HttpRequest,
     params: web::Query<FeedQueryParameter>,
+    pagination: Pagination,
+) -> ApiResponse {
+    let conn = state.read_conn()?;
+    let current_user = auth::get_current_user(&req)?;
+    let Pagination { limit, offset } = pagination;
+    let before = params
+        .before
+        .as_deref()
+        .map(str::parse::<service::FeedCursor>)
+        .transpose()?;
+    let offset = if before.is_some() { 0 } else { offset };
+    let (articles_list, articles_count, next_cursor) = state.timed(&req, "article.feed", || {
+        service::fetch_following_articles(
+            &conn,
+            &service::FetchFollowedArticlesSerivce {
+                current_user,
+                offset,
+                limit,
+                before,
+            },
+        )
+    })?;
+
+    let res = MultipleArticlesResponse::from((articles_list, articles_count));
+    let mut builder = HttpResponse::Ok();
+    builder
+        .insert_header((header::X_TOTAL_COUNT, articles_count.to_string()))
+        .insert_header((header::X_LIMIT, limit.to_string()))
+        .insert_header((header::X_OFFSET, offset.to_string()));
+    if let Some(next_cursor) = next_cursor {
+        builder.insert_header((header::X_NEXT_CURSOR, next_cursor.to_string()));
+    }
+    Ok(builder.json(res))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/profiles/{username}/articles",
+    tag = "article",
+    params(
+        ("username" = String, Path, description = "Username of the author"),
+        pagination::PaginationQuery,
+    ),
+    responses(
+        (status = 200, description = "Articles written by the author", body = MultipleArticlesResponse),
+        (status = 400, description = "Invalid pagination", body = crate::middleware::error::ErrorResponse),
+        (status = 404, description = "Author not found", body = crate::middleware::error::ErrorResponse),
+    ),
+)]
+pub async fn index_by_author(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<UsernameSlug>,
+    pagination: Pagination,
+) -> ApiResponse {
+    let conn = state.read_conn()?;
+    let current_user = auth::get_current_user(&req).ok();
+    let username = path.into_inner();
+    let Pagination { limit, offset } = pagination;
+    let (articles_list, articles_count) =
+        service::list_by_author(&conn, &username, limit, offset, current_user.as_ref())?;
+
+    let res = MultipleArticlesResponse::from((articles_list, articles_count));
+    Ok(HttpResponse::Ok()
+        .insert_header((header::X_TOTAL_COUNT, articles_count.to_string()))
+        .insert_header((header::X_LIMIT, limit.to_string()))
+        .insert_header((header::X_OFFSET, offset.to_string()))
+        .json(res))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/articles/tag/{tag_name}",
+    tag = "article",
+    params(
+        ("tag_name" = String, Path, description = "Name of the tag"),
+        pagination::PaginationQuery,
+    ),
+    responses(
+        (status = 200, description = "Articles with the tag", body = MultipleArticlesResponse),
+        (status = 400, description = "Invalid pagination", body = crate::middleware::error::ErrorResponse),
+    ),
+)]
+pub async fn index_by_tag(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<TagName>,
+    pagination: Pagination,
+) -> ApiResponse {
+    let conn = state.read_conn()?;
+    let current_user = auth::get_current_user(&req).ok();
+    let tag_name = path.into_inner();
+    let Pagination { limit, offset } = pagination;
+    let (articles_list, articles_count) =
+        service::list_by_tag(&conn, &tag_name, limit, offset, current_user.as_ref())?;
+
+    let res = MultipleArticlesResponse::from((articles_list, articles_count));
+    Ok(HttpResponse::Ok()
+        .insert_header((header::X_TOTAL_COUNT, articles_count.to_string()))
+        .insert_header((header::X_LIMIT, limit.to_string()))
+        .insert_header((header::X_OFFSET, offset.to_string()))
+        .json(res))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/articles/batch",
+    tag = "article",
+    request_body = request::BatchArticlesRequest,
+    responses(
+        (status = 200, description = "Requested articles in the requested order", body = MultipleArticlesResponse),
+        (status = 401, description = "Unauthorized", body = crate::middleware::error::ErrorResponse),
+        (status = 422, description = "Too many slugs", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
+pub async fn batch(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Json<request::BatchArticlesRequest>,
 ) -> ApiResponse {
-    let conn = state.get_conn()?;
+    form.validate()?;
+    let conn = state.read_conn()?;
     let current_user = auth::get_current_user(&req)?;
-    let offset = std::cmp::min(params.offset.to_owned().unwrap_or(0), 100);
-    let limit = params.limit.unwrap_or(20);
-    let (articles_list, articles_count) = service::fetch_following_articles(
+    let articles_list = service::fetch_articles_by_slugs(
         &conn,
-        &service::FetchFollowedArticlesSerivce {
+        &service::FetchArticlesBySlugs {
             current_user,
-            offset,
-            limit,
+            slugs: form.slugs.clone(),
         },
     )?;
-
+    let articles_count = articles_list.len() as i64;
     let res = MultipleArticlesResponse::from((articles_list, articles_count));
     Ok(HttpResponse::Ok().json(res))
 }
 
-pub async fn show(state: web::Data<AppState>, path: web::Path<ArticleTitleSlug>) -> ApiResponse {
-    let conn = state.get_conn()?;
+#[utoipa::path(
+    get,
+    path = "/api/articles/{article_title_slug}",
+    tag = "article",
+    params(("article_title_slug" = String, Path, description = "Slug of the article")),
+    responses(
+        (status = 200, description = "Article", body = SingleArticleResponse),
+        (status = 304, description = "Article unchanged since the `If-None-Match` ETag"),
+        (status = 404, description = "Article not found", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
+pub async fn show(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<ArticleTitleSlug>,
+) -> ApiResponse {
+    let conn = state.read_conn()?;
     let article_title_slug = path.into_inner();
-    let (article, profile, favorite_info, tags_list) =
-        service::fetch_article_by_slug(&conn, &service::FetchArticleBySlug { article_title_slug })?;
-    let res = SingleArticleResponse::from((article, profile, favorite_info, tags_list));
-    Ok(HttpResponse::Ok().json(res))
+    let (article, profile, favorite_info, tags_list, comments_count) =
+        state.timed(&req, "article.show", || {
+            service::fetch_article_by_slug(
+                &conn,
+                &service::FetchArticleBySlug { article_title_slug },
+            )
+        })?;
+    let article_id = article.id;
+    let mut res =
+        SingleArticleResponse::from((article, profile, favorite_info, tags_list, comments_count));
+    // The view count changes on every fetch, so it is left out of the ETag.
+    let view_count = std::mem::take(&mut res.article.view_count);
+    let etag = etag::of(&res)?;
+    if etag::is_fresh(&req, &etag) {
+        return Ok(HttpResponse::NotModified()
+            .insert_header(ETag(etag))
+            .finish());
+    }
+    // Read-only mode serves the stored count rather than writing a new one.
+    res.article.view_count = if state.config.track_views && !state.is_read_only() {
+        Article::increment_view_count(&state.write_conn()?, &article_id)?
+    } else {
+        view_count
+    };
+    Ok(HttpResponse::Ok().insert_header(ETag(etag)).json(res))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/articles",
+    tag = "article",
+    request_body = request::CreateArticleRequest,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Repeating a key answers with the article it created instead of creating another"),
+    ),
+    responses(
+        (status = 201, description = "Created article, with its URL in `Location`", body = SingleArticleResponse),
+        (status = 400, description = "Invalid idempotency key", body = crate::middleware::error::ErrorResponse),
+        (status = 401, description = "Unauthorized", body = crate::middleware::error::ErrorResponse),
+        (status = 409, description = "Request with the idempotency key is still in progress", body = crate::middleware::error::ErrorResponse),
+        (status = 422, description = "Invalid article, or idempotency key used with another request", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
 pub async fn create(
     state: web::Data<AppState>,
     req: HttpRequest,
     form: web::Json<request::CreateArticleRequest>,
 ) -> ApiResponse {
-    let conn = state.get_conn()?;
+    form.validate(&state.config)?;
+    let conn = state.write_conn()?;
     let current_user = auth::get_current_user(&req)?;
-    let (article, profile, favorite_info, tag_list) = service::create(
-        &conn,
+    let key = match idempotency_key(&req)? {
+        Some(key) => key,
+        None => {
+            let res = create_article(&conn, &state.config, &form, current_user.id)?;
+            return Ok(created(&state.config, &res.article.slug, &res));
+        }
+    };
+    let user_id = current_user.id;
+    let request_hash = request_hash(&*form)?;
+    // NOTE: retried as a whole, the create nested within only gets a savepoint and isn't retried.
+    let res = db::with_retry(&conn, constants::TRANSACTION_ATTEMPTS, |conn| {
+        if !IdempotencyKey::claim(conn, &user_id, &key, &request_hash)? {
+            return IdempotencyKey::find_response(conn, &user_id, &key, &request_hash)?.ok_or_else(
+                || {
+                    AppError::Conflict(json!(ErrorResponse::from(
+                        "request with this key is still in progress"
+                    )))
+                },
+            );
+        }
+        let res = create_article(conn, &state.config, &form, user_id)?;
+        let res = serde_json::to_value(res).map_err(|_| AppError::InternalServerError)?;
+        IdempotencyKey::save_response(conn, &user_id, &key, &res)?;
+        Ok(res)
+    })?;
+    let slug = res["article"]["slug"].as_str().unwrap_or_default();
+    Ok(created(&state.config, slug, &res))
+}
+
+// A replayed idempotent create answers the same as the original, Location included.
+fn created<T: Serialize>(config: &Config, slug: &str, body: &T) -> HttpResponse {
+    let location = format!(
+        "{}/api/articles/{}",
+        config.public_base_url.as_deref().unwrap_or_default(),
+        slug
+    );
+    HttpResponse::Created()
+        .insert_header((LOCATION, location))
+        .json(body)
+}
+
+fn create_article(
+    conn: &PgConnection,
+    config: &Config,
+    form: &request::CreateArticleRequest,
+    author_id: Uuid,
+) -> Result<SingleArticleResponse, AppError> {
+    let (article, profile, favorite_info, tag_list, comments_count) = service::create(
+        conn,
         &service::CreateArticleSerivce {
             title: form.article.title.clone(),
-            slug: Article::convert_title_to_slug(&form.article.title),
+            slug: Article::convert_title_to_slug(&form.article.title, config.slug_max_length),
             description: form.article.description.clone(),
             body: form.article.body.clone(),
             tag_name_list: form.article.tag_list.to_owned(),
-            current_user,
+            author_id,
         },
     )?;
-    let res = SingleArticleResponse::from((article, profile, favorite_info, tag_list));
+    Ok(SingleArticleResponse::from((
+        article,
+        profile,
+        favorite_info,
+        tag_list,
+        comments_count,
+    )))
+}
+
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
+fn idempotency_key(req: &HttpRequest) -> Result<Option<String>, AppError> {
+    let value = match req.headers().get(header::IDEMPOTENCY_KEY) {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    match value.to_str() {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LENGTH => {
+            Ok(Some(key.to_owned()))
+        }
+        _ => {
+            let message = format!(
+                "{} must be 1 to {} visible ASCII characters",
+                header::IDEMPOTENCY_KEY,
+                MAX_IDEMPOTENCY_KEY_LENGTH
+            );
+            Err(AppError::BadRequest(json!(ErrorResponse::from(
+                message.as_str()
+            ))))
+        }
+    }
+}
+
+// Ties an idempotency key to the request it was first used with.
+fn request_hash<T: Serialize>(form: &T) -> Result<String, AppError> {
+    let bytes = serde_json::to_vec(form).map_err(|_| AppError::InternalServerError)?;
+    Ok(format!("{:x}", Sha256::digest(&bytes)))
+}
+
+// Dry run of `create`: validates the draft the same way, but writes nothing.
+#[utoipa::path(
+    post,
+    path = "/api/articles/validate",
+    tag = "article",
+    request_body = request::CreateArticleRequest,
+    responses(
+        (status = 200, description = "Valid draft and the slug it would get", body = ArticleDraftResponse),
+        (status = 401, description = "Unauthorized", body = crate::middleware::error::ErrorResponse),
+        (status = 422, description = "Invalid article", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
+pub async fn validate(
+    state: web::Data<AppState>,
+    form: web::Json<request::CreateArticleRequest>,
+) -> ApiResponse {
+    form.validate(&state.config)?;
+    let slug = Article::convert_title_to_slug(&form.article.title, state.config.slug_max_length);
+    let res = ArticleDraftResponse {
+        slug: Some(slug).filter(|slug| !slug.is_empty()),
+    };
     Ok(HttpResponse::Ok().json(res))
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/articles/{article_title_slug}",
+    tag = "article",
+    params(("article_title_slug" = String, Path, description = "Slug of the article")),
+    request_body = request::UpdateArticleRequest,
+    responses(
+        (status = 200, description = "Updated article", body = SingleArticleResponse),
+        (status = 401, description = "Unauthorized", body = crate::middleware::error::ErrorResponse),
+        (status = 403, description = "Not the author", body = crate::middleware::error::ErrorResponse),
+        (status = 404, description = "Article not found", body = crate::middleware::error::ErrorResponse),
+        (status = 422, description = "Invalid article", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
 pub async fn update(
     state: web::Data<AppState>,
     req: HttpRequest,
     path: web::Path<ArticleTitleSlug>,
     form: web::Json<request::UpdateArticleRequest>,
 ) -> ApiResponse {
-    let conn = state.get_conn()?;
+    form.validate(&state.config)?;
+    let conn = state.write_conn()?;
     let current_user = auth::get_current_user(&req)?;
     let article_title_slug = path.into_inner();
     let article_slug = &form
         .article
         .title
         .as_ref()
-        .map(|_title| Article::convert_title_to_slug(_title));
+        .map(|_title| Article::convert_title_to_slug(_title, state.config.slug_max_length));
 
-    let (article, profile, favorite_info, tag_list) = service::update_article(
+    let (article, profile, favorite_info, tag_list, comments_count) = service::update_article(
         &conn,
         &service::UpdateArticleService {
             current_user,
@@ -127,27 +479,75 @@ pub async fn update(
             title: form.article.title.clone(),
             description: form.article.description.clone(),
             body: form.article.body.clone(),
+            tag_name_list: form.article.tag_list.clone(),
         },
     )?;
 
-    let res = SingleArticleResponse::from((article, profile, favorite_info, tag_list));
+    let res =
+        SingleArticleResponse::from((article, profile, favorite_info, tag_list, comments_count));
     Ok(HttpResponse::Ok().json(res))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/articles/{article_title_slug}",
+    tag = "article",
+    params(("article_title_slug" = String, Path, description = "Slug of the article")),
+    responses(
+        (status = 200, description = "Article deleted"),
+        (status = 401, description = "Unauthorized", body = crate::middleware::error::ErrorResponse),
+        (status = 403, description = "Not the author", body = crate::middleware::error::ErrorResponse),
+        (status = 404, description = "Article not found", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
 pub async fn delete(
     state: web::Data<AppState>,
     req: HttpRequest,
     path: web::Path<ArticleTitleSlug>,
 ) -> ApiResponse {
-    let conn = state.get_conn()?;
+    let conn = state.write_conn()?;
     let current_user = auth::get_current_user(&req)?;
     let article_title_slug = path.into_inner();
-    let _ = Article::delete(
+    service::delete_article(
         &conn,
-        &DeleteArticle {
-            slug: article_title_slug,
-            author_id: current_user.id,
+        &service::DeleteArticleService {
+            current_user,
+            article_title_slug,
+            soft_delete: state.config.soft_delete,
         },
     )?;
     Ok(HttpResponse::Ok().json(()))
 }
+
+#[utoipa::path(
+    post,
+    path = "/api/articles/bulk-delete",
+    tag = "article",
+    request_body = request::BulkDeleteArticlesRequest,
+    responses(
+        (status = 200, description = "Deleted slugs, and the ones left alone because they were someone else's or not found", body = BulkDeleteArticlesResponse),
+        (status = 401, description = "Unauthorized", body = crate::middleware::error::ErrorResponse),
+        (status = 422, description = "Too many slugs", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
+pub async fn bulk_delete(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Json<request::BulkDeleteArticlesRequest>,
+) -> ApiResponse {
+    form.validate()?;
+    let conn = state.write_conn()?;
+    let current_user = auth::get_current_user(&req)?;
+    let result = service::bulk_delete_articles(
+        &conn,
+        &service::BulkDeleteArticlesService {
+            current_user,
+            slugs: form.slugs.clone(),
+            soft_delete: state.config.soft_delete,
+        },
+    )?;
+    let res = BulkDeleteArticlesResponse::from(result);
+    Ok(HttpResponse::Ok().json(res))
+}
@@ -1,8 +1,9 @@
+use crate::app::profile::model::Profile;
 use crate::app::user::model::User;
 use crate::error::AppError;
-use crate::schema::articles;
+use crate::schema::{articles, users};
 use crate::utils::converter;
-use chrono::NaiveDateTime;
+use chrono::{DateTime, Utc};
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
 use diesel::Insertable;
@@ -19,8 +20,10 @@ pub struct Article {
     pub title: String,
     pub description: String,
     pub body: String,
-    pub created_at: NaiveDateTime,
-    pub updated_at: NaiveDateTime,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub view_count: i64,
 }
 
 impl Article {
@@ -32,24 +35,60 @@ impl Article {
         Ok(article)
     }
 
+    // Same as `create`, but with the id chosen by the caller instead of the database.
+    pub fn create_with_id(
+        conn: &PgConnection,
+        id: &Uuid,
+        record: &CreateArticle,
+    ) -> Result<Self, AppError> {
+        let article = diesel::insert_into(articles::table)
+            .values((articles::id.eq(id), record))
+            .get_result::<Article>(conn)?;
+
+        Ok(article)
+    }
+
     pub fn update(
         conn: &PgConnection,
-        article_title_slug: &str,
-        author_id: &Uuid,
+        article_id: &Uuid,
         record: &UpdateArticle,
     ) -> Result<Self, AppError> {
         let article = diesel::update(
             articles::table
-                .filter(articles::slug.eq(article_title_slug))
-                .filter(articles::author_id.eq_all(author_id)),
+                .filter(articles::id.eq(article_id))
+                .filter(articles::deleted_at.is_null()),
         )
         .set(record)
         .get_result::<Article>(conn)?;
         Ok(article)
     }
 
-    pub fn convert_title_to_slug(title: &str) -> String {
-        converter::to_kebab(title)
+    // Backed by the unique index that `slug TEXT UNIQUE` creates (articles_slug_key).
+    pub fn find_by_slug(conn: &PgConnection, slug: &str) -> Result<Self, AppError> {
+        let article = articles::table
+            .filter(articles::slug.eq(slug))
+            .filter(articles::deleted_at.is_null())
+            .first::<Self>(conn)?;
+        Ok(article)
+    }
+
+    // Incremented in SQL, so concurrent views are never lost. Returns the new count.
+    pub fn increment_view_count(conn: &PgConnection, id: &Uuid) -> Result<i64, AppError> {
+        let view_count = diesel::update(articles::table.find(id))
+            .set(articles::view_count.eq(articles::view_count + 1))
+            .returning(articles::view_count)
+            .get_result::<i64>(conn)?;
+        Ok(view_count)
+    }
+
+    // Empty when nothing in the title has an ASCII form, see `fallback_slug`.
+    pub fn convert_title_to_slug(title: &str, max_length: usize) -> String {
+        converter::truncate_slug(&converter::to_slug(title), max_length).to_owned()
+    }
+
+    // Slug for titles without any letters or digits, e.g. all emoji.
+    pub fn fallback_slug(id: &Uuid) -> String {
+        id.to_simple().to_string()[..8].to_owned()
     }
 
     pub fn fetch_by_slug_and_author_id(
@@ -59,6 +98,7 @@ impl Article {
         let item = articles::table
             .filter(articles::slug.eq_all(params.slug.to_owned()))
             .filter(articles::author_id.eq_all(params.author_id))
+            .filter(articles::deleted_at.is_null())
             .first::<Self>(conn)?;
         Ok(item)
     }
@@ -71,6 +111,7 @@ impl Article {
         let result = articles::table
             .inner_join(users::table)
             .filter(articles::slug.eq(slug))
+            .filter(articles::deleted_at.is_null())
             .get_result::<(Self, User)>(conn)?;
         Ok(result)
     }
@@ -83,6 +124,7 @@ impl Article {
         let ids = users::table
             .inner_join(articles::table)
             .filter(users::username.eq(name))
+            .filter(articles::deleted_at.is_null())
             .select(articles::id)
             .load::<Uuid>(conn)?;
         Ok(ids)
@@ -93,19 +135,34 @@ impl Article {
         let result = articles::table
             .inner_join(users::table)
             .filter(articles::id.eq(id))
+            .filter(articles::deleted_at.is_null())
             .get_result::<(Article, User)>(conn)?;
         Ok(result)
     }
 
-    pub fn delete(conn: &PgConnection, params: &DeleteArticle) -> Result<(), AppError> {
-        let _ = diesel::delete(
+    pub fn delete(conn: &PgConnection, article_id: &Uuid) -> Result<(), AppError> {
+        let _ =
+            diesel::delete(articles::table.filter(articles::id.eq(article_id))).execute(conn)?;
+        // NOTE: references tag rows are deleted automatically by DELETE CASCADE
+
+        Ok(())
+    }
+
+    // Keeps the row (and its comments, favorites and tags) and only hides it from queries.
+    // The slug gets the id appended, so a new article can take it over.
+    pub fn soft_delete(conn: &PgConnection, article_id: &Uuid) -> Result<(), AppError> {
+        let _ = diesel::update(
             articles::table
-                .filter(articles::slug.eq(&params.slug))
-                .filter(articles::author_id.eq(params.author_id)),
+                .filter(articles::id.eq(article_id))
+                .filter(articles::deleted_at.is_null()),
         )
+        .set((
+            articles::deleted_at.eq(diesel::dsl::now),
+            articles::slug.eq(diesel::dsl::sql::<diesel::sql_types::Text>(
+                "slug || '-deleted-' || id",
+            )),
+        ))
         .execute(conn)?;
-        // NOTE: references tag rows are deleted automatically by DELETE CASCADE
-
         Ok(())
     }
 }
@@ -155,12 +212,133 @@ pub struct UpdateArticle {
     pub body: Option<String>,
 }
 
-pub struct FetchBySlugAndAuthorId {
+impl UpdateArticle {
+    // Diesel refuses to run an UPDATE without any column to set.
+    pub fn is_empty(&self) -> bool {
+        self.slug.is_none()
+            && self.title.is_none()
+            && self.description.is_none()
+            && self.body.is_none()
+    }
+}
+
+// What a list needs of an article and its author. Unlike `(Article, User)`, neither the
+// body nor the author's password are ever read from the database.
+#[derive(Queryable, Debug, Clone)]
+pub struct ArticlePreview {
+    pub id: Uuid,
     pub slug: String,
+    pub title: String,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub view_count: i64,
     pub author_id: Uuid,
+    pub author_username: String,
+    pub author_bio: Option<String>,
+    pub author_image: Option<String>,
+}
+
+type ArticlePreviewColumns = (
+    articles::id,
+    articles::slug,
+    articles::title,
+    articles::description,
+    articles::created_at,
+    articles::updated_at,
+    articles::view_count,
+    articles::author_id,
+    users::username,
+    users::bio,
+    users::image,
+);
+
+impl ArticlePreview {
+    // To be selected from `articles` joined with `users`.
+    pub const COLUMNS: ArticlePreviewColumns = (
+        articles::id,
+        articles::slug,
+        articles::title,
+        articles::description,
+        articles::created_at,
+        articles::updated_at,
+        articles::view_count,
+        articles::author_id,
+        users::username,
+        users::bio,
+        users::image,
+    );
+
+    pub fn author_profile(&self, following: bool) -> Profile {
+        Profile {
+            username: self.author_username.to_owned(),
+            bio: self.author_bio.to_owned(),
+            image: self.author_image.to_owned(),
+            following,
+        }
+    }
 }
 
-pub struct DeleteArticle {
+pub struct FetchBySlugAndAuthorId {
     pub slug: String,
     pub author_id: Uuid,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::db::establish_test_connection;
+    use crate::utils::test_support::{create_article, signup_user};
+
+    #[test]
+    fn fallback_slug_is_short_id() {
+        let id = Uuid::parse_str("8e1f2b3c-4d5e-4f60-8a7b-9c0d1e2f3a4b").unwrap();
+        assert_eq!(Article::convert_title_to_slug("🎉 !!!", 80), "");
+        assert_eq!(Article::fallback_slug(&id), "8e1f2b3c");
+    }
+
+    #[test]
+    fn long_title_slug_fits_max_length() {
+        let title = "A very long title ".repeat(17);
+        assert!(title.len() >= 300);
+        let slug = Article::convert_title_to_slug(&title, 80);
+        assert!(slug.len() <= 80);
+        assert!(slug.starts_with("a-very-long-title-a-very"));
+        assert!(!slug.ends_with('-'));
+        assert!(title.to_lowercase().contains(&slug.replace('-', " ")));
+    }
+
+    #[test]
+    fn preview_selects_neither_body_nor_password() {
+        let query = articles::table
+            .inner_join(users::table)
+            .select(ArticlePreview::COLUMNS);
+        let sql = diesel::debug_query::<diesel::pg::Pg, _>(&query).to_string();
+
+        assert!(sql.contains("\"articles\".\"slug\""));
+        assert!(!sql.contains("\"body\""));
+        assert!(!sql.contains("\"password\""));
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn find_by_slug_returns_article() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "find-by-slug");
+        let article = create_article(&conn, user.id, "find-by-slug", &[]);
+
+        let found = Article::find_by_slug(&conn, "find-by-slug").unwrap();
+
+        assert_eq!(found.id, article.id);
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn find_by_unknown_slug_is_not_found() {
+        let conn = establish_test_connection();
+
+        let result = Article::find_by_slug(&conn, "no-article-has-this-slug");
+
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+}
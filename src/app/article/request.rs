@@ -1,27 +1,275 @@
+use crate::app::tag::model::Tag;
+use crate::config::Config;
+use crate::error::AppError;
+use crate::utils::trimmed;
+use crate::utils::validator::ValidationErrors;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use utoipa::ToSchema;
 
-#[derive(Deserialize, Serialize)]
+const MAX_BATCH_SLUGS: usize = 50;
+
+#[derive(Deserialize, Serialize, ToSchema)]
 pub struct CreateArticleRequest {
     pub article: CreateArticleInner,
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateArticleInner {
+    #[serde(deserialize_with = "trimmed::deserialize")]
     pub title: String,
+    #[serde(deserialize_with = "trimmed::deserialize")]
     pub description: String,
+    // Kept verbatim, since leading whitespace is meaningful in markdown.
     pub body: String,
     pub tag_list: Option<Vec<String>>,
 }
 
-#[derive(Deserialize, Serialize)]
+impl CreateArticleRequest {
+    pub fn validate(&self, config: &Config) -> Result<(), AppError> {
+        let mut errors = ValidationErrors::default();
+        for (field, value) in [
+            ("title", &self.article.title),
+            ("description", &self.article.description),
+            ("body", &self.article.body),
+        ] {
+            if value.trim().is_empty() {
+                errors.add(field, "can't be blank");
+            }
+        }
+        validate_body(&mut errors, &self.article.body, config);
+        validate_tag_list(&mut errors, &self.article.tag_list, config);
+        errors.into_result()
+    }
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
 pub struct UpdateArticleRequest {
     pub article: UpdateArticleInner,
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
 pub struct UpdateArticleInner {
+    #[serde(default, deserialize_with = "trimmed::deserialize_option")]
     pub title: Option<String>,
+    #[serde(default, deserialize_with = "trimmed::deserialize_option")]
     pub description: Option<String>,
     pub body: Option<String>,
+    // Omitted means unchanged, while an empty list removes every tag.
+    pub tag_list: Option<Vec<String>>,
+}
+
+impl UpdateArticleRequest {
+    pub fn validate(&self, config: &Config) -> Result<(), AppError> {
+        let mut errors = ValidationErrors::default();
+        if let Some(body) = &self.article.body {
+            validate_body(&mut errors, body, config);
+        }
+        validate_tag_list(&mut errors, &self.article.tag_list, config);
+        errors.into_result()
+    }
+}
+
+fn validate_body(errors: &mut ValidationErrors, body: &str, config: &Config) {
+    if body.len() > config.max_article_body_length {
+        errors.add(
+            "body",
+            &format!(
+                "is too long (maximum is {} bytes)",
+                config.max_article_body_length
+            ),
+        );
+    }
+}
+
+// Duplicates (ignoring case) are stored once, so they don't count towards the maximum.
+fn validate_tag_list(
+    errors: &mut ValidationErrors,
+    tag_list: &Option<Vec<String>>,
+    config: &Config,
+) {
+    let count = tag_list
+        .iter()
+        .flatten()
+        .map(|name| Tag::normalize_name(name))
+        .collect::<HashSet<_>>()
+        .len();
+    if count > config.max_tags_per_article {
+        errors.add(
+            "tagList",
+            &format!(
+                "is too long (maximum is {} tags)",
+                config.max_tags_per_article
+            ),
+        );
+    }
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct BatchArticlesRequest {
+    pub slugs: Vec<String>,
+}
+
+impl BatchArticlesRequest {
+    pub fn validate(&self) -> Result<(), AppError> {
+        validate_slugs(&self.slugs)
+    }
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct BulkDeleteArticlesRequest {
+    pub slugs: Vec<String>,
+}
+
+impl BulkDeleteArticlesRequest {
+    pub fn validate(&self) -> Result<(), AppError> {
+        validate_slugs(&self.slugs)
+    }
+}
+
+fn validate_slugs(slugs: &[String]) -> Result<(), AppError> {
+    let mut errors = ValidationErrors::default();
+    if slugs.len() > MAX_BATCH_SLUGS {
+        errors.add(
+            "slugs",
+            &format!("is too long (maximum is {} slugs)", MAX_BATCH_SLUGS),
+        );
+    }
+    errors.into_result()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch(count: usize) -> BatchArticlesRequest {
+        BatchArticlesRequest {
+            slugs: (0..count).map(|i| format!("slug-{}", i)).collect(),
+        }
+    }
+
+    fn tag_list(count: usize) -> Option<Vec<String>> {
+        Some((0..count).map(|i| format!("tag-{}", i)).collect())
+    }
+
+    fn create(tag_list: Option<Vec<String>>) -> CreateArticleRequest {
+        CreateArticleRequest {
+            article: CreateArticleInner {
+                title: "title".to_owned(),
+                description: "description".to_owned(),
+                body: "body".to_owned(),
+                tag_list,
+            },
+        }
+    }
+
+    fn update(tag_list: Option<Vec<String>>) -> UpdateArticleRequest {
+        UpdateArticleRequest {
+            article: UpdateArticleInner {
+                title: None,
+                description: None,
+                body: None,
+                tag_list,
+            },
+        }
+    }
+
+    #[test]
+    fn create_accepts_up_to_max_tags() {
+        let config = Config::default();
+        assert!(create(None).validate(&config).is_ok());
+        assert!(create(tag_list(config.max_tags_per_article))
+            .validate(&config)
+            .is_ok());
+    }
+
+    #[test]
+    fn create_rejects_too_many_tags() {
+        let config = Config::default();
+        match create(tag_list(config.max_tags_per_article + 1)).validate(&config) {
+            Err(AppError::UnprocessableEntity(body)) => assert_eq!(
+                body,
+                serde_json::json!({"errors": {"tagList": ["is too long (maximum is 10 tags)"]}})
+            ),
+            _ => panic!("expected unprocessable entity"),
+        }
+    }
+
+    #[test]
+    fn create_rejects_blank_fields() {
+        let request = CreateArticleRequest {
+            article: CreateArticleInner {
+                title: " ".to_owned(),
+                description: "".to_owned(),
+                body: "body".to_owned(),
+                tag_list: None,
+            },
+        };
+        match request.validate(&Config::default()) {
+            Err(AppError::UnprocessableEntity(body)) => assert_eq!(
+                body,
+                serde_json::json!({"errors": {
+                    "description": ["can't be blank"],
+                    "title": ["can't be blank"],
+                }})
+            ),
+            _ => panic!("expected unprocessable entity"),
+        }
+    }
+
+    #[test]
+    fn rejects_too_long_body() {
+        let config = Config {
+            max_article_body_length: 4,
+            ..Config::default()
+        };
+        let mut request = create(None);
+        request.article.body = "1234".to_owned();
+        assert!(request.validate(&config).is_ok());
+        request.article.body = "12345".to_owned();
+        assert!(request.validate(&config).is_err());
+
+        let mut request = update(None);
+        request.article.body = Some("12345".to_owned());
+        assert!(request.validate(&config).is_err());
+    }
+
+    #[test]
+    fn update_rejects_too_many_tags() {
+        let config = Config::default();
+        assert!(update(None).validate(&config).is_ok());
+        assert!(update(tag_list(config.max_tags_per_article + 1))
+            .validate(&config)
+            .is_err());
+    }
+
+    #[test]
+    fn duplicate_tags_count_once() {
+        let config = Config::default();
+        let mut tags = vec!["rust".to_owned(); config.max_tags_per_article];
+        tags.push("Rust".to_owned());
+        assert!(create(Some(tags)).validate(&config).is_ok());
+    }
+
+    #[test]
+    fn batch_accepts_up_to_max_slugs() {
+        assert!(batch(MAX_BATCH_SLUGS).validate().is_ok());
+    }
+
+    #[test]
+    fn batch_rejects_too_many_slugs() {
+        assert!(batch(MAX_BATCH_SLUGS + 1).validate().is_err());
+    }
+
+    #[test]
+    fn bulk_delete_rejects_too_many_slugs() {
+        let request = BulkDeleteArticlesRequest {
+            slugs: (0..=MAX_BATCH_SLUGS)
+                .map(|i| format!("slug-{}", i))
+                .collect(),
+        };
+        assert!(request.validate().is_err());
+    }
 }
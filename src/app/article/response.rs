@@ -1,28 +1,44 @@
 use crate::app::article::model::Article;
+use crate::app::article::service::{ArticleWithRelations, BulkDeleted};
 use crate::app::favorite::model::FavoriteInfo;
 use crate::app::profile::model::Profile;
 use crate::app::tag::model::Tag;
 use crate::utils::date::Iso8601;
 use serde::{Deserialize, Serialize};
 use std::convert::From;
+use utoipa::ToSchema;
 
 type ArticleCount = i64;
+type CommentsCount = i64;
 
-#[derive(Deserialize, Serialize)]
+// `slug` is `null` when nothing in the title has an ASCII form, in which case the article
+// gets a slug from its id on create.
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct ArticleDraftResponse {
+    pub slug: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
 pub struct SingleArticleResponse {
     pub article: ArticleContent,
 }
 
-impl From<(Article, Profile, FavoriteInfo, Vec<Tag>)> for SingleArticleResponse {
+impl From<(Article, Profile, FavoriteInfo, Vec<Tag>, CommentsCount)> for SingleArticleResponse {
     fn from(
-        (article, profile, favorite_info, tag_list): (Article, Profile, FavoriteInfo, Vec<Tag>),
+        (article, profile, favorite_info, tag_list, comments_count): (
+            Article,
+            Profile,
+            FavoriteInfo,
+            Vec<Tag>,
+            CommentsCount,
+        ),
     ) -> Self {
         Self {
             article: ArticleContent {
                 slug: article.slug,
                 title: article.title,
                 description: article.description,
-                body: article.body,
+                body: Some(article.body),
                 tag_list: tag_list
                     .iter()
                     .map(move |tag| tag.name.to_owned())
@@ -31,6 +47,8 @@ impl From<(Article, Profile, FavoriteInfo, Vec<Tag>)> for SingleArticleResponse
                 updated_at: Iso8601(article.updated_at),
                 favorited: favorite_info.is_favorited.to_owned(),
                 favorites_count: favorite_info.favorites_count.to_owned(),
+                comments_count,
+                view_count: article.view_count,
                 author: AuthorContent {
                     username: profile.username,
                     bio: profile.bio,
@@ -42,30 +60,16 @@ impl From<(Article, Profile, FavoriteInfo, Vec<Tag>)> for SingleArticleResponse
     }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct MultipleArticlesResponse {
     pub articles: Vec<ArticleContent>,
     pub articles_count: ArticleCount,
 }
 
-type ArticlesCount = i64;
-type Inner = ((Article, Profile, FavoriteInfo), Vec<Tag>);
-type ArticlesList = Vec<Inner>;
-type Item = (ArticlesList, ArticlesCount);
-impl From<Item> for MultipleArticlesResponse {
-    fn from((list, articles_count): (Vec<Inner>, ArticleCount)) -> Self {
-        let articles = list
-            .iter()
-            .map(|((article, profile, favorite_info), tags_list)| {
-                ArticleContent::from((
-                    article.to_owned(),
-                    profile.to_owned(),
-                    favorite_info.to_owned(),
-                    tags_list.to_owned(),
-                ))
-            })
-            .collect();
+impl From<(Vec<ArticleWithRelations>, ArticleCount)> for MultipleArticlesResponse {
+    fn from((list, articles_count): (Vec<ArticleWithRelations>, ArticleCount)) -> Self {
+        let articles = list.into_iter().map(ArticleContent::from).collect();
         Self {
             articles_count,
             articles,
@@ -73,35 +77,48 @@ impl From<Item> for MultipleArticlesResponse {
     }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ArticleContent {
     pub slug: String,
     pub title: String,
     pub description: String,
-    pub body: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
     pub tag_list: Vec<String>,
+    #[schema(value_type = String)]
     pub created_at: Iso8601,
+    #[schema(value_type = String)]
     pub updated_at: Iso8601,
     pub favorited: bool,
     pub favorites_count: i64,
+    pub comments_count: i64,
+    pub view_count: i64,
     pub author: AuthorContent,
 }
 
-impl From<(Article, Profile, FavoriteInfo, Vec<Tag>)> for ArticleContent {
+impl From<(Article, Profile, FavoriteInfo, Vec<Tag>, CommentsCount)> for ArticleContent {
     fn from(
-        (article, profile, favorite_info, tag_list): (Article, Profile, FavoriteInfo, Vec<Tag>),
+        (article, profile, favorite_info, tag_list, comments_count): (
+            Article,
+            Profile,
+            FavoriteInfo,
+            Vec<Tag>,
+            CommentsCount,
+        ),
     ) -> Self {
         Self {
             slug: article.slug,
             title: article.title,
             description: article.description,
-            body: article.body,
+            body: Some(article.body),
             tag_list: tag_list.iter().map(move |tag| tag.name.clone()).collect(),
             created_at: Iso8601(article.created_at),
             updated_at: Iso8601(article.updated_at),
             favorited: favorite_info.is_favorited.to_owned(),
             favorites_count: favorite_info.favorites_count.to_owned(),
+            comments_count,
+            view_count: article.view_count,
             author: AuthorContent {
                 username: profile.username,
                 bio: profile.bio,
@@ -112,10 +129,67 @@ impl From<(Article, Profile, FavoriteInfo, Vec<Tag>)> for ArticleContent {
     }
 }
 
-#[derive(Deserialize, Serialize)]
+impl From<ArticleWithRelations> for ArticleContent {
+    fn from(
+        ArticleWithRelations {
+            article,
+            author,
+            tags,
+            favorited,
+            favorites_count,
+            comments_count,
+        }: ArticleWithRelations,
+    ) -> Self {
+        Self {
+            slug: article.slug,
+            title: article.title,
+            description: article.description,
+            body: None,
+            tag_list: tags.into_iter().map(|tag| tag.name).collect(),
+            created_at: Iso8601(article.created_at),
+            updated_at: Iso8601(article.updated_at),
+            favorited,
+            favorites_count,
+            comments_count,
+            view_count: article.view_count,
+            author: AuthorContent {
+                username: author.username,
+                bio: author.bio,
+                image: author.image,
+                following: author.following,
+            },
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
 pub struct AuthorContent {
     pub username: String,
     pub bio: Option<String>,
     pub image: Option<String>,
     pub following: bool,
 }
+
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkDeleteArticlesResponse {
+    pub deleted: Vec<String>,
+    pub forbidden: Vec<String>,
+    pub not_found: Vec<String>,
+}
+
+impl From<BulkDeleted> for BulkDeleteArticlesResponse {
+    fn from(
+        BulkDeleted {
+            deleted,
+            forbidden,
+            not_found,
+        }: BulkDeleted,
+    ) -> Self {
+        Self {
+            deleted,
+            forbidden,
+            not_found,
+        }
+    }
+}
@@ -1,14 +1,21 @@
-use crate::app::article::model::{Article, CreateArticle, UpdateArticle};
+use crate::app::article::model::{Article, ArticlePreview, CreateArticle, UpdateArticle};
+use crate::app::comment::model::Comment;
 use crate::app::favorite::model::{Favorite, FavoriteInfo};
 use crate::app::follow::model::Follow;
 use crate::app::profile::model::Profile;
 use crate::app::tag::model::{CreateTag, Tag};
 use crate::app::user::model::User;
+use crate::constants;
 use crate::error::AppError;
-use crate::schema::articles::dsl::*;
+use crate::middleware::error::ErrorResponse;
 use crate::schema::{articles, tags, users};
+use crate::utils::db;
+use chrono::{DateTime, TimeZone, Utc};
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
+use serde_json::json;
+use std::fmt;
+use std::str::FromStr;
 use uuid::Uuid;
 
 pub struct CreateArticleSerivce {
@@ -17,30 +24,43 @@ pub struct CreateArticleSerivce {
     pub description: String,
     pub body: String,
     pub tag_name_list: Option<Vec<String>>,
-    pub current_user: User,
+    pub author_id: Uuid,
 }
 pub fn create(
     conn: &PgConnection,
     params: &CreateArticleSerivce,
-) -> Result<(Article, Profile, FavoriteInfo, Vec<Tag>), AppError> {
-    let article = Article::create(
-        conn,
-        &CreateArticle {
-            author_id: params.current_user.id,
-            slug: params.slug.clone(),
-            title: params.title.clone(),
-            description: params.description.clone(),
-            body: params.body.clone(),
-        },
-    )?;
-    let tag_list = create_tag_list(conn, &params.tag_name_list, &article.id)?;
-
-    let profile = params
-        .current_user
-        .fetch_profile(conn, &article.author_id)?;
+) -> Result<(Article, Profile, FavoriteInfo, Vec<Tag>, CommentsCount), AppError> {
+    let id = Uuid::new_v4();
+    let slug = if params.slug.is_empty() {
+        Article::fallback_slug(&id)
+    } else {
+        params.slug.clone()
+    };
+    let (article, author, tag_list) =
+        db::with_retry(conn, constants::TRANSACTION_ATTEMPTS, |conn| {
+            let article = Article::create_with_id(
+                conn,
+                &id,
+                &CreateArticle {
+                    author_id: params.author_id,
+                    slug: slug.clone(),
+                    title: params.title.clone(),
+                    description: params.description.clone(),
+                    body: params.body.clone(),
+                },
+            )?;
+            let tag_list = create_tag_list(conn, &params.tag_name_list, &article.id)?;
+            // Read back in the same transaction, so the author is the row as persisted
+            // rather than whatever the caller authenticated with.
+            let (article, author) = Article::find_with_author(conn, &article.id)?;
+            Ok((article, author, tag_list))
+        })?;
+
+    // The author is the current user, who can't follow themselves.
+    let profile = Profile::from_user(&author, false);
 
     let favorite_info = {
-        let is_favorited = article.is_favorited_by_user_id(conn, &params.current_user.id)?;
+        let is_favorited = article.is_favorited_by_user_id(conn, &author.id)?;
         let favorites_count = article.fetch_favorites_count(conn)?;
         FavoriteInfo {
             is_favorited,
@@ -48,7 +68,9 @@ pub fn create(
         }
     };
 
-    Ok((article, profile, favorite_info, tag_list))
+    let comments_count = Comment::count_by_article_id(conn, &article.id)?;
+
+    Ok((article, profile, favorite_info, tag_list, comments_count))
 }
 
 fn create_tag_list(
@@ -59,7 +81,13 @@ fn create_tag_list(
     let list = tag_name_list
         .as_ref()
         .map(|tag_name_list| {
-            let records = tag_name_list
+            let mut seen = std::collections::HashSet::new();
+            let names = tag_name_list
+                .iter()
+                .map(|name| Tag::normalize_name(name))
+                .filter(|name| seen.insert(name.clone()))
+                .collect::<Vec<_>>();
+            let records = names
                 .iter()
                 .map(|name| CreateTag { name, article_id })
                 .collect();
@@ -78,35 +106,54 @@ pub struct FetchArticlesList {
 }
 
 type ArticlesCount = i64;
-type ArticlesListInner = (Article, Profile, FavoriteInfo);
-type ArticlesList = Vec<(ArticlesListInner, Vec<Tag>)>;
-pub fn fetch_articles_list(
+pub type CommentsCount = i64;
+// An article of a list, with everything its response needs resolved for the viewer.
+// `author.following` and `favorited` are false without a viewer.
+#[derive(Clone)]
+pub struct ArticleWithRelations {
+    pub article: ArticlePreview,
+    pub author: Profile,
+    pub tags: Vec<Tag>,
+    pub favorited: bool,
+    pub favorites_count: i64,
+    pub comments_count: CommentsCount,
+}
+
+type ArticlesList = Vec<ArticleWithRelations>;
+pub fn list(
     conn: &PgConnection,
     params: FetchArticlesList,
+    current_user: Option<&User>,
 ) -> Result<(ArticlesList, ArticlesCount), AppError> {
     use diesel::prelude::*;
+    let tagged_ids = match &params.tag {
+        Some(tag_name) => Some(Tag::fetch_ids_by_name(conn, tag_name)?),
+        None => None,
+    };
+    let authored_ids = match &params.author {
+        Some(author_name) => Some(Article::fetch_ids_by_author_name(conn, author_name)?),
+        None => None,
+    };
+    let favorited_ids = match &params.favorited {
+        Some(username) => Some(Favorite::fetch_favorited_article_ids_by_username(
+            conn, username,
+        )?),
+        None => None,
+    };
     let create_query = || {
-        let mut query = articles::table.inner_join(users::table).into_boxed();
-
-        if let Some(tag_name) = &params.tag {
-            let ids = Tag::fetch_ids_by_name(conn, tag_name)
-                .expect("could not fetch tagged article ids."); // TODO: use ? or error handling
-            query = query.filter(articles::id.eq_any(ids));
+        let mut query = articles::table
+            .inner_join(users::table)
+            .filter(articles::deleted_at.is_null())
+            .into_boxed();
+        if let Some(ids) = &tagged_ids {
+            query = query.filter(articles::id.eq_any(ids.clone()));
         }
-
-        if let Some(author_name) = &params.author {
-            let ids = Article::fetch_ids_by_author_name(conn, author_name)
-                .expect("could not fetch authors id."); // TODO: use ? or error handling
-            query = query.filter(articles::id.eq_any(ids));
+        if let Some(ids) = &authored_ids {
+            query = query.filter(articles::id.eq_any(ids.clone()));
         }
-
-        if let Some(username) = &params.favorited {
-            let ids = Favorite::fetch_favorited_article_ids_by_username(conn, username)
-                .expect("could not fetch favorited articles id."); // TODO: use ? or error handling
-
-            query = query.filter(articles::id.eq_any(ids));
+        if let Some(ids) = &favorited_ids {
+            query = query.filter(articles::id.eq_any(ids.clone()));
         }
-
         query
     };
 
@@ -115,59 +162,176 @@ pub fn fetch_articles_list(
         .first::<i64>(conn)?;
 
     let list = {
-        let article_and_user_list = create_query()
+        let previews = create_query()
+            .order((articles::created_at.desc(), articles::id.desc()))
             .offset(params.offset)
             .limit(params.limit)
-            .load::<(Article, User)>(conn)?;
+            .select(ArticlePreview::COLUMNS)
+            .load::<ArticlePreview>(conn)?;
+        to_articles_list(conn, previews, current_user)?
+    };
 
-        let tags_list = {
-            let articles_list = article_and_user_list
-                .clone()
-                .into_iter()
-                .map(|(article, _)| article)
-                .collect::<Vec<_>>();
-            let tags_list = Tag::belonging_to(&articles_list)
-                .order(tags::name.asc())
-                .load::<Tag>(conn)?;
-            let tags_list: Vec<Vec<Tag>> = tags_list.grouped_by(&articles_list);
-            tags_list
-        };
+    Ok((list, articles_count))
+}
 
-        let favorites_count_list = {
-            let list: Result<Vec<_>, _> = article_and_user_list
-                .clone()
-                .into_iter()
-                .map(|(article, _)| article.fetch_favorites_count(conn))
-                .collect();
+pub fn list_by_author(
+    conn: &PgConnection,
+    username: &str,
+    limit: i64,
+    offset: i64,
+    current_user: Option<&User>,
+) -> Result<(ArticlesList, ArticlesCount), AppError> {
+    // Unknown author is 404, while a known author without articles is an empty list.
+    let author = User::find_by_username(conn, username)?;
 
-            list?
-        };
+    let create_query = || {
+        articles::table
+            .inner_join(users::table)
+            .filter(articles::author_id.eq(author.id))
+            .filter(articles::deleted_at.is_null())
+    };
+
+    let articles_count = create_query()
+        .select(diesel::dsl::count(articles::id))
+        .first::<i64>(conn)?;
 
-        article_and_user_list
-            .into_iter()
-            .zip(favorites_count_list)
-            .map(|((article, user), favorites_count)| {
-                (
-                    article,
-                    Profile {
-                        username: user.username,
-                        bio: user.bio,
-                        image: user.image,
-                        following: false, // NOTE: because not authz
-                    },
-                    FavoriteInfo {
-                        is_favorited: false, // NOTE: because not authz
-                        favorites_count,
-                    },
-                )
-            })
-            .zip(tags_list)
-            .collect::<Vec<_>>()
+    let list = {
+        let previews = create_query()
+            .order(articles::created_at.desc())
+            .offset(offset)
+            .limit(limit)
+            .select(ArticlePreview::COLUMNS)
+            .load::<ArticlePreview>(conn)?;
+        to_articles_list(conn, previews, current_user)?
     };
 
     Ok((list, articles_count))
 }
 
+// An unknown tag is an empty list, not 404, since tags only exist through their articles.
+pub fn list_by_tag(
+    conn: &PgConnection,
+    tag_name: &str,
+    limit: i64,
+    offset: i64,
+    current_user: Option<&User>,
+) -> Result<(ArticlesList, ArticlesCount), AppError> {
+    // A subquery rather than a join, so an article tagged twice is still listed once.
+    let create_query = || {
+        articles::table
+            .inner_join(users::table)
+            .filter(
+                articles::id.eq_any(
+                    tags::table
+                        .filter(tags::name.eq(Tag::normalize_name(tag_name)))
+                        .select(tags::article_id),
+                ),
+            )
+            .filter(articles::deleted_at.is_null())
+    };
+
+    let articles_count = create_query()
+        .select(diesel::dsl::count(articles::id))
+        .first::<i64>(conn)?;
+
+    let list = {
+        let previews = create_query()
+            .order(articles::created_at.desc())
+            .offset(offset)
+            .limit(limit)
+            .select(ArticlePreview::COLUMNS)
+            .load::<ArticlePreview>(conn)?;
+        to_articles_list(conn, previews, current_user)?
+    };
+
+    Ok((list, articles_count))
+}
+
+// `current_user` is the viewer, so `favorited` in the response is about them, not `username`.
+pub fn list_favorited_by(
+    conn: &PgConnection,
+    username: &str,
+    current_user: Option<&User>,
+    limit: i64,
+    offset: i64,
+) -> Result<(ArticlesList, ArticlesCount), AppError> {
+    use crate::schema::favorites;
+    let favoriter_id = users::table
+        .filter(users::username.eq(username))
+        .select(users::id)
+        .first::<Uuid>(conn)
+        .optional()?;
+    let favoriter_id = match favoriter_id {
+        Some(id) => id,
+        None => return Ok((vec![], 0)),
+    };
+
+    let create_query = || {
+        articles::table
+            .inner_join(users::table)
+            .inner_join(favorites::table)
+            .filter(favorites::user_id.eq(favoriter_id))
+            .filter(articles::deleted_at.is_null())
+    };
+
+    let articles_count = create_query()
+        .select(diesel::dsl::count(articles::id))
+        .first::<i64>(conn)?;
+
+    let previews = create_query()
+        .select(ArticlePreview::COLUMNS)
+        .order(favorites::created_at.desc())
+        .offset(offset)
+        .limit(limit)
+        .load::<ArticlePreview>(conn)?;
+
+    let list = to_articles_list(conn, previews, current_user)?;
+
+    Ok((list, articles_count))
+}
+
+fn to_articles_list(
+    conn: &PgConnection,
+    previews: Vec<ArticlePreview>,
+    current_user: Option<&User>,
+) -> Result<ArticlesList, AppError> {
+    let ids = previews
+        .iter()
+        .map(|article| article.id)
+        .collect::<Vec<_>>();
+    let mut tags_map = Tag::fetch_by_article_ids(conn, &ids)?;
+    let comments_count_map = Comment::count_by_article_ids(conn, &ids)?;
+    let favorites_count_map = Favorite::count_by_article_ids(conn, &ids)?;
+
+    let (followee_ids, favorited_article_ids) = match current_user {
+        Some(current_user) => {
+            let author_ids = previews
+                .iter()
+                .map(|article| article.author_id)
+                .collect::<Vec<_>>();
+            (
+                User::followees_among(conn, &current_user.id, &author_ids)?,
+                current_user.fetch_favorited_article_ids(conn)?,
+            )
+        }
+        None => Default::default(),
+    };
+
+    let list = previews
+        .into_iter()
+        .map(|article| ArticleWithRelations {
+            author: article.author_profile(followee_ids.contains(&article.author_id)),
+            tags: tags_map.remove(&article.id).unwrap_or_default(),
+            favorited: favorited_article_ids.contains(&article.id),
+            favorites_count: favorites_count_map.get(&article.id).copied().unwrap_or(0),
+            comments_count: comments_count_map.get(&article.id).copied().unwrap_or(0),
+            article,
+        })
+        .collect::<Vec<_>>();
+
+    Ok(list)
+}
+
 pub struct FetchArticle {
     pub article_id: Uuid,
     pub current_user: User,
@@ -178,10 +342,10 @@ pub fn fetch_article(
         article_id,
         current_user,
     }: &FetchArticle,
-) -> Result<(Article, Profile, FavoriteInfo, Vec<Tag>), AppError> {
+) -> Result<(Article, Profile, FavoriteInfo, Vec<Tag>, CommentsCount), AppError> {
     let (article, author) = Article::find_with_author(conn, article_id)?;
 
-    let profile = current_user.fetch_profile(conn, &author.id)?;
+    let profile = current_user.fetch_profile(conn, &author)?;
 
     let favorite_info = {
         let is_favorited = article.is_favorited_by_user_id(conn, &current_user.id)?;
@@ -194,7 +358,9 @@ pub fn fetch_article(
 
     let tags_list = Tag::belonging_to(&article).load::<Tag>(conn)?;
 
-    Ok((article, profile, favorite_info, tags_list))
+    let comments_count = Comment::count_by_article_id(conn, &article.id)?;
+
+    Ok((article, profile, favorite_info, tags_list, comments_count))
 }
 
 pub struct FetchArticleBySlug {
@@ -203,10 +369,10 @@ pub struct FetchArticleBySlug {
 pub fn fetch_article_by_slug(
     conn: &PgConnection,
     params: &FetchArticleBySlug,
-) -> Result<(Article, Profile, FavoriteInfo, Vec<Tag>), AppError> {
+) -> Result<(Article, Profile, FavoriteInfo, Vec<Tag>, CommentsCount), AppError> {
     let (article, author) = Article::fetch_by_slug_with_author(conn, &params.article_title_slug)?;
 
-    let profile = author.fetch_profile(conn, &author.id)?;
+    let profile = Profile::from_user(&author, false);
 
     let tags_list = Tag::belonging_to(&article).load::<Tag>(conn)?;
 
@@ -219,107 +385,144 @@ pub fn fetch_article_by_slug(
         }
     };
 
-    Ok((article, profile, favorite_info, tags_list))
+    let comments_count = Comment::count_by_article_id(conn, &article.id)?;
+
+    Ok((article, profile, favorite_info, tags_list, comments_count))
+}
+
+// Position of an article in the feed, handed to clients as an opaque `<micros>.<id>` string.
+// Unlike a slug, it stays valid after the article is renamed or deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeedCursor {
+    created_at: DateTime<Utc>,
+    id: Uuid,
+}
+
+impl FeedCursor {
+    fn of(article: &ArticlePreview) -> Self {
+        Self {
+            created_at: article.created_at,
+            id: article.id,
+        }
+    }
+}
+
+impl fmt::Display for FeedCursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}",
+            self.created_at.timestamp_micros(),
+            self.id.to_simple()
+        )
+    }
+}
+
+impl FromStr for FeedCursor {
+    type Err = AppError;
+
+    fn from_str(cursor: &str) -> Result<Self, Self::Err> {
+        let invalid = || AppError::BadRequest(json!(ErrorResponse::from("invalid feed cursor")));
+        let (micros, id) = cursor.split_once('.').ok_or_else(invalid)?;
+        let micros = micros.parse::<i64>().map_err(|_| invalid())?;
+        let created_at = Utc
+            .timestamp_opt(
+                micros.div_euclid(1_000_000),
+                (micros.rem_euclid(1_000_000) * 1_000) as u32,
+            )
+            .single()
+            .ok_or_else(invalid)?;
+        let id = Uuid::parse_str(id).map_err(|_| invalid())?;
+        Ok(Self { created_at, id })
+    }
 }
 
-use crate::schema::follows;
 pub struct FetchFollowedArticlesSerivce {
     pub current_user: User,
     pub offset: i64,
     pub limit: i64,
+    pub before: Option<FeedCursor>,
 }
+// Also returns the cursor of the last article when the page is full.
 pub fn fetch_following_articles(
     conn: &PgConnection,
     params: &FetchFollowedArticlesSerivce,
-) -> Result<(ArticlesList, ArticlesCount), AppError> {
-    let create_query = {
-        let ids = Follow::fetch_folowee_ids_by_follower_id(conn, &params.current_user.id)?;
-        articles.filter(articles::author_id.eq_any(ids))
+) -> Result<(ArticlesList, ArticlesCount, Option<FeedCursor>), AppError> {
+    let followee_ids = Follow::fetch_folowee_ids_by_follower_id(conn, &params.current_user.id)?;
+    let create_query = || {
+        articles::table
+            .inner_join(users::table)
+            .filter(articles::author_id.eq_any(followee_ids.clone()))
+            .filter(articles::deleted_at.is_null())
+            .into_boxed()
     };
 
-    let articles_list = {
-        let article_and_user_list = create_query
-            .to_owned()
-            .inner_join(users::table)
+    // Keyset pagination. Only articles strictly older than the cursor in
+    // (created_at desc, id desc) order are returned.
+    let (articles_list, next_cursor) = {
+        let mut query = create_query();
+        if let Some(cursor) = params.before {
+            query = query.filter(
+                articles::created_at
+                    .lt(cursor.created_at)
+                    .or(articles::created_at
+                        .eq(cursor.created_at)
+                        .and(articles::id.lt(cursor.id))),
+            );
+        }
+        let previews = query
+            .order((articles::created_at.desc(), articles::id.desc()))
             .limit(params.limit)
             .offset(params.offset)
-            .order(articles::created_at.desc())
-            .get_results::<(Article, User)>(conn)?;
+            .select(ArticlePreview::COLUMNS)
+            .load::<ArticlePreview>(conn)?;
 
-        let tags_list = {
-            let articles_list = article_and_user_list
-                .clone() // TODO: avoid clone
-                .into_iter()
-                .map(|(article, _)| article)
-                .collect::<Vec<_>>();
-
-            let tags_list = Tag::belonging_to(&articles_list).load::<Tag>(conn)?;
-            let tags_list: Vec<Vec<Tag>> = tags_list.grouped_by(&articles_list);
-            tags_list
+        let next_cursor = if previews.len() as i64 == params.limit {
+            previews.last().map(FeedCursor::of)
+        } else {
+            None
         };
+        let articles_list = to_articles_list(conn, previews, Some(&params.current_user))?;
+        (articles_list, next_cursor)
+    };
 
-        let follows_list = {
-            let user_ids_list = article_and_user_list
-                .clone() // TODO: avoid clone
-                .into_iter()
-                .map(|(_, user)| user.id)
-                .collect::<Vec<_>>();
-
-            let list = follows::table
-                .filter(follows::follower_id.eq(params.current_user.id))
-                .filter(follows::followee_id.eq_any(user_ids_list))
-                .get_results::<Follow>(conn)?;
-
-            list.into_iter()
-        };
+    let articles_count = create_query()
+        .select(diesel::dsl::count(articles::id))
+        .first::<i64>(conn)?;
 
-        let favorites_count_list = {
-            let list: Result<Vec<_>, _> = article_and_user_list
-                .clone()
-                .into_iter()
-                .map(|(article, _)| article.fetch_favorites_count(conn))
-                .collect();
+    Ok((articles_list, articles_count, next_cursor))
+}
 
-            list?
-        };
+pub struct FetchArticlesBySlugs {
+    pub current_user: User,
+    pub slugs: Vec<String>,
+}
+pub fn fetch_articles_by_slugs(
+    conn: &PgConnection,
+    params: &FetchArticlesBySlugs,
+) -> Result<ArticlesList, AppError> {
+    let found_list = articles::table
+        .inner_join(users::table)
+        .filter(articles::slug.eq_any(&params.slugs))
+        .filter(articles::deleted_at.is_null())
+        .select(ArticlePreview::COLUMNS)
+        .load::<ArticlePreview>(conn)?;
 
-        let favorited_article_ids = params.current_user.fetch_favorited_article_ids(conn)?;
-        let is_favorited_by_me = |article: &Article| {
-            favorited_article_ids
+    // Keep the requested order, skipping unknown and repeated slugs.
+    let mut seen = std::collections::HashSet::new();
+    let previews = params
+        .slugs
+        .iter()
+        .filter(|slug| seen.insert(slug.as_str()))
+        .filter_map(|slug| {
+            found_list
                 .iter()
-                .copied()
-                .any(|_id| _id == article.id)
-        };
-
-        article_and_user_list
-            .into_iter()
-            .zip(favorites_count_list)
-            .map(|((article, user), favorites_count)| {
-                let following = follows_list.clone().any(|item| item.followee_id == user.id);
-                let is_favorited = is_favorited_by_me(&article);
-                (
-                    article,
-                    Profile {
-                        username: user.username,
-                        bio: user.bio,
-                        image: user.image,
-                        following: following.to_owned(),
-                    },
-                    FavoriteInfo {
-                        is_favorited,
-                        favorites_count,
-                    },
-                )
-            })
-            .zip(tags_list)
-            .collect::<Vec<_>>()
-    };
-
-    let articles_count = create_query
-        .select(diesel::dsl::count(articles::id))
-        .first::<i64>(conn)?;
+                .find(|article| &article.slug == slug)
+                .cloned()
+        })
+        .collect::<Vec<_>>();
 
-    Ok((articles_list, articles_count))
+    to_articles_list(conn, previews, Some(&params.current_user))
 }
 
 pub struct UpdateArticleService {
@@ -329,28 +532,39 @@ pub struct UpdateArticleService {
     pub title: Option<String>,
     pub description: Option<String>,
     pub body: Option<String>,
+    pub tag_name_list: Option<Vec<String>>,
 }
 pub fn update_article(
     conn: &PgConnection,
     params: &UpdateArticleService,
-) -> Result<(Article, Profile, FavoriteInfo, Vec<Tag>), AppError> {
-    let article = Article::update(
-        conn,
-        &params.article_title_slug,
-        &params.current_user.id,
-        &UpdateArticle {
-            slug: params.slug.to_owned(),
-            title: params.title.to_owned(),
-            description: params.description.to_owned(),
-            body: params.body.to_owned(),
-        },
-    )?;
-
-    let tag_list = Tag::fetch_by_article_id(conn, article.id)?;
-
-    let profile = params
-        .current_user
-        .fetch_profile(conn, &article.author_id)?;
+) -> Result<(Article, Profile, FavoriteInfo, Vec<Tag>, CommentsCount), AppError> {
+    let article = find_own_article(conn, &params.article_title_slug, &params.current_user)?;
+    let record = UpdateArticle {
+        slug: params.slug.as_ref().map(|slug| {
+            if slug.is_empty() {
+                Article::fallback_slug(&article.id)
+            } else {
+                slug.to_owned()
+            }
+        }),
+        title: params.title.to_owned(),
+        description: params.description.to_owned(),
+        body: params.body.to_owned(),
+    };
+    let (article, tag_list) = db::with_retry(conn, constants::TRANSACTION_ATTEMPTS, |conn| {
+        let article = if record.is_empty() {
+            article.clone()
+        } else {
+            Article::update(conn, &article.id, &record)?
+        };
+        let tag_list = match &params.tag_name_list {
+            Some(tag_name_list) => update_tags(conn, &article.id, tag_name_list)?,
+            None => Tag::fetch_by_article_id(conn, article.id)?,
+        };
+        Ok((article, tag_list))
+    })?;
+
+    let profile = Profile::from_user(&params.current_user, false);
 
     let favorite_info = {
         let is_favorited = article.is_favorited_by_user_id(conn, &params.current_user.id)?;
@@ -361,5 +575,494 @@ pub fn update_article(
         }
     };
 
-    Ok((article, profile, favorite_info, tag_list))
+    let comments_count = Comment::count_by_article_id(conn, &article.id)?;
+
+    Ok((article, profile, favorite_info, tag_list, comments_count))
+}
+
+pub struct DeleteArticleService {
+    pub current_user: User,
+    pub article_title_slug: String,
+    pub soft_delete: bool,
+}
+pub fn delete_article(conn: &PgConnection, params: &DeleteArticleService) -> Result<(), AppError> {
+    let article = find_own_article(conn, &params.article_title_slug, &params.current_user)?;
+    delete_by_id(conn, &article.id, params.soft_delete)
+}
+
+fn delete_by_id(conn: &PgConnection, article_id: &Uuid, soft_delete: bool) -> Result<(), AppError> {
+    if soft_delete {
+        Article::soft_delete(conn, article_id)
+    } else {
+        Article::delete(conn, article_id)
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BulkDeleted {
+    pub deleted: Vec<String>,
+    pub forbidden: Vec<String>,
+    pub not_found: Vec<String>,
+}
+
+pub struct BulkDeleteArticlesService {
+    pub current_user: User,
+    pub slugs: Vec<String>,
+    pub soft_delete: bool,
+}
+// Deletes the articles the current user wrote, reporting the others instead of failing on them.
+// Any other error rolls back every deletion.
+pub fn bulk_delete_articles(
+    conn: &PgConnection,
+    params: &BulkDeleteArticlesService,
+) -> Result<BulkDeleted, AppError> {
+    conn.transaction::<_, AppError, _>(|| {
+        let mut seen = std::collections::HashSet::new();
+        let mut result = BulkDeleted::default();
+        for slug in &params.slugs {
+            if !seen.insert(slug) {
+                continue;
+            }
+            match find_own_article(conn, slug, &params.current_user) {
+                Ok(article) => {
+                    delete_by_id(conn, &article.id, params.soft_delete)?;
+                    result.deleted.push(slug.to_owned());
+                }
+                Err(AppError::Forbidden(_)) => result.forbidden.push(slug.to_owned()),
+                Err(AppError::NotFound(_)) => result.not_found.push(slug.to_owned()),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(result)
+    })
+}
+
+// Unknown slug is 404, while someone else's article is 403.
+fn find_own_article(
+    conn: &PgConnection,
+    article_title_slug: &str,
+    current_user: &User,
+) -> Result<Article, AppError> {
+    let article = Article::find_by_slug(conn, article_title_slug)?;
+    if article.author_id != current_user.id {
+        return Err(AppError::Forbidden(json!(ErrorResponse::from(
+            "only the author can modify the article"
+        ))));
+    }
+    Ok(article)
+}
+
+// Replaces the tags of the article with `tag_name_list`. Tags which are kept are left untouched.
+pub fn update_tags(
+    conn: &PgConnection,
+    article_id: &Uuid,
+    tag_name_list: &[String],
+) -> Result<Vec<Tag>, AppError> {
+    let tag_name_list = tag_name_list
+        .iter()
+        .map(|name| Tag::normalize_name(name))
+        .collect::<Vec<_>>();
+    db::with_retry(conn, constants::TRANSACTION_ATTEMPTS, |conn| {
+        let current_tags = Tag::fetch_by_article_id(conn, *article_id)?;
+
+        let removed_ids = current_tags
+            .iter()
+            .filter(|tag| !tag_name_list.contains(&tag.name))
+            .map(|tag| tag.id)
+            .collect::<Vec<_>>();
+        if !removed_ids.is_empty() {
+            Tag::delete_list(conn, removed_ids)?;
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        let added_names = tag_name_list
+            .iter()
+            .filter(|name| seen.insert(name.as_str()))
+            .filter(|name| !current_tags.iter().any(|tag| &tag.name == *name))
+            .collect::<Vec<_>>();
+        if !added_names.is_empty() {
+            let records = added_names
+                .into_iter()
+                .map(|name| CreateTag { name, article_id })
+                .collect();
+            Tag::create_list(conn, records)?;
+        }
+
+        Tag::fetch_by_article_id(conn, *article_id)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::db::establish_test_connection;
+    use crate::utils::test_support::{create_article, signup_user};
+
+    #[test]
+    fn feed_cursor_round_trips() {
+        let cursor = FeedCursor {
+            created_at: Utc.timestamp_opt(1_700_000_000, 123_456_000).unwrap(),
+            id: Uuid::new_v4(),
+        };
+        assert_eq!(cursor.to_string().parse::<FeedCursor>().unwrap(), cursor);
+    }
+
+    #[test]
+    fn feed_cursor_rejects_garbage() {
+        for cursor in [
+            "",
+            "some-slug",
+            "12.not-a-uuid",
+            "x.00000000000000000000000000000000",
+        ] {
+            assert!(matches!(
+                cursor.parse::<FeedCursor>(),
+                Err(AppError::BadRequest(_))
+            ));
+        }
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn soft_deleted_article_is_hidden_from_list_but_persists() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "soft-delete");
+        let article = create_article(&conn, user.id, "soft-delete-test", &[]);
+
+        Article::soft_delete(&conn, &article.id).unwrap();
+
+        let (articles, count) = list(
+            &conn,
+            FetchArticlesList {
+                tag: None,
+                author: Some(user.username.clone()),
+                favorited: None,
+                offset: 0,
+                limit: 20,
+            },
+            None,
+        )
+        .unwrap();
+        assert!(articles.is_empty());
+        assert_eq!(count, 0);
+
+        let persisted = articles::table
+            .find(article.id)
+            .first::<Article>(&conn)
+            .unwrap();
+        assert!(persisted.deleted_at.is_some());
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn soft_deleted_article_frees_its_slug() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "soft-delete-slug");
+        let article = CreateArticle {
+            author_id: user.id,
+            slug: "soft-delete-slug".to_owned(),
+            title: "soft delete slug".to_owned(),
+            description: "description".to_owned(),
+            body: "body".to_owned(),
+        };
+        let deleted = Article::create(&conn, &article).unwrap();
+
+        Article::soft_delete(&conn, &deleted.id).unwrap();
+        let recreated = Article::create(&conn, &article).unwrap();
+
+        assert_eq!(recreated.slug, "soft-delete-slug");
+        let persisted = articles::table
+            .find(deleted.id)
+            .first::<Article>(&conn)
+            .unwrap();
+        assert_eq!(
+            persisted.slug,
+            format!("soft-delete-slug-deleted-{}", deleted.id)
+        );
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn list_by_author_without_articles_is_empty() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "no-articles");
+
+        let (list, count) = list_by_author(&conn, &user.username, 20, 0, None).unwrap();
+
+        assert!(list.is_empty());
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn list_by_unknown_author_is_not_found() {
+        let conn = establish_test_connection();
+
+        let result = list_by_author(&conn, "nobody-has-this-name", 20, 0, None);
+
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn update_is_rolled_back_when_tags_fail() {
+        let conn = establish_test_connection();
+        let author = signup_user(&conn, "update-rollback");
+        let article = create_article(&conn, author.id, "update-rollback", &["update-rollback"]);
+        let current_user = author;
+
+        // Postgres rejects NUL in text, so replacing the tags fails after the update.
+        let result = update_article(
+            &conn,
+            &UpdateArticleService {
+                current_user,
+                article_title_slug: article.slug.clone(),
+                slug: None,
+                title: Some("updated title".to_owned()),
+                description: None,
+                body: None,
+                tag_name_list: Some(vec!["bad\0tag".to_owned()]),
+            },
+        );
+
+        assert!(result.is_err());
+        let persisted = articles::table
+            .find(article.id)
+            .first::<Article>(&conn)
+            .unwrap();
+        assert_eq!(persisted.title, article.title);
+        let tags = Tag::fetch_by_article_id(&conn, article.id).unwrap();
+        assert_eq!(tag_names(&tags), ["update-rollback"]);
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn create_returns_author_as_persisted() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "persisted-author");
+        // The row changes after `user` was loaded, as if updated by a concurrent request.
+        diesel::update(users::table.find(user.id))
+            .set(users::bio.eq("updated bio"))
+            .execute(&conn)
+            .unwrap();
+
+        let (article, profile, _, _, _) = create(
+            &conn,
+            &CreateArticleSerivce {
+                slug: "persisted-author-article".to_owned(),
+                title: "persisted author article".to_owned(),
+                description: "description".to_owned(),
+                body: "body".to_owned(),
+                tag_name_list: None,
+                author_id: user.id,
+            },
+        )
+        .unwrap();
+
+        let author = User::find(&conn, article.author_id).unwrap();
+        assert_eq!(article.author_id, user.id);
+        assert_eq!(profile.username, author.username);
+        assert_eq!(profile.bio.as_deref(), Some("updated bio"));
+        assert_eq!(profile.image, author.image);
+        assert!(!profile.following);
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn list_keeps_each_article_with_its_own_author_and_tags() {
+        let conn = establish_test_connection();
+        let expected: [(&str, &[&str]); 3] = [
+            ("relations-first", &["relations-a", "relations-b"]),
+            ("relations-second", &[]),
+            ("relations-third", &["relations-c"]),
+        ];
+        for (slug, tags) in expected {
+            let author = signup_user(&conn, slug);
+            create_article(&conn, author.id, slug, tags);
+        }
+
+        let (articles, _count) = list(
+            &conn,
+            FetchArticlesList {
+                tag: None,
+                author: None,
+                favorited: None,
+                offset: 0,
+                limit: 1000,
+            },
+            None,
+        )
+        .unwrap();
+
+        for (slug, tags) in expected {
+            let listed = articles
+                .iter()
+                .find(|listed| listed.article.slug == slug)
+                .unwrap();
+            assert_eq!(listed.author.username, slug);
+            assert_eq!(tag_names(&listed.tags), tags);
+        }
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn create_stores_duplicate_tags_once() {
+        let conn = establish_test_connection();
+        let author = signup_user(&conn, "create-duplicate-tags");
+        let (article, _, _, _, _) = create(
+            &conn,
+            &CreateArticleSerivce {
+                slug: "create-duplicate-tags".to_owned(),
+                title: "create duplicate tags".to_owned(),
+                description: "description".to_owned(),
+                body: "body".to_owned(),
+                tag_name_list: Some(vec![
+                    "rust".to_owned(),
+                    "diesel".to_owned(),
+                    "rust".to_owned(),
+                ]),
+                author_id: author.id,
+            },
+        )
+        .unwrap();
+
+        let tags = Tag::fetch_by_article_id(&conn, article.id).unwrap();
+
+        assert_eq!(tag_names(&tags), vec!["diesel", "rust"]);
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn tags_differing_in_case_are_the_same_tag() {
+        let conn = establish_test_connection();
+        let author = signup_user(&conn, "tag-case");
+        let (upper, _, _, _, _) = create(
+            &conn,
+            &CreateArticleSerivce {
+                slug: "tag-case-upper".to_owned(),
+                title: "tag case upper".to_owned(),
+                description: "description".to_owned(),
+                body: "body".to_owned(),
+                tag_name_list: Some(vec!["Rust".to_owned(), " RUST ".to_owned()]),
+                author_id: author.id,
+            },
+        )
+        .unwrap();
+        let lower = create_article(&conn, author.id, "tag-case-lower", &["rust"]);
+
+        assert_eq!(
+            tag_names(&Tag::fetch_by_article_id(&conn, upper.id).unwrap()),
+            vec!["rust"]
+        );
+        let (list, count) = list_by_tag(&conn, "RUST", 20, 0, None).unwrap();
+        let ids = list
+            .iter()
+            .map(|listed| listed.article.id)
+            .collect::<Vec<_>>();
+        assert!(ids.contains(&upper.id) && ids.contains(&lower.id));
+        assert!(count >= 2);
+    }
+
+    fn tag_names(tags: &[Tag]) -> Vec<String> {
+        let mut names = tags.iter().map(|tag| tag.name.clone()).collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn update_tags_adds_new_tags_and_keeps_existing_ones() {
+        let conn = establish_test_connection();
+        let author = signup_user(&conn, "update-tags-add");
+        let article = create_article(&conn, author.id, "update-tags-add", &["rust"]);
+        let before = Tag::fetch_by_article_id(&conn, article.id).unwrap();
+
+        let tags = update_tags(
+            &conn,
+            &article.id,
+            &["rust".to_owned(), "diesel".to_owned()],
+        )
+        .unwrap();
+
+        assert_eq!(tag_names(&tags), vec!["diesel", "rust"]);
+        let kept = tags.iter().find(|tag| tag.name == "rust").unwrap();
+        assert_eq!(kept.id, before[0].id);
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn update_tags_removes_missing_tags() {
+        let conn = establish_test_connection();
+        let author = signup_user(&conn, "update-tags-remove");
+        let article = create_article(&conn, author.id, "update-tags-remove", &["rust", "diesel"]);
+
+        let tags = update_tags(&conn, &article.id, &["rust".to_owned()]).unwrap();
+
+        assert_eq!(tag_names(&tags), vec!["rust"]);
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn update_article_without_tag_list_keeps_tags() {
+        let conn = establish_test_connection();
+        let author = signup_user(&conn, "update-tags-omit");
+        let article = create_article(&conn, author.id, "update-tags-omit", &["rust", "diesel"]);
+        let current_user = author;
+
+        let (_, _, _, tags, _) = update_article(
+            &conn,
+            &UpdateArticleService {
+                current_user,
+                article_title_slug: article.slug.clone(),
+                slug: None,
+                title: None,
+                description: None,
+                body: Some("new body".to_owned()),
+                tag_name_list: None,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(tag_names(&tags), vec!["diesel", "rust"]);
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn list_favorited_by_user_without_favorites_is_empty() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "no-favorites");
+
+        let (list, count) = list_favorited_by(&conn, &user.username, None, 20, 0).unwrap();
+
+        assert!(list.is_empty());
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn list_favorited_by_reflects_viewer_not_target() {
+        use crate::app::favorite::model::CreateFavorite;
+        let conn = establish_test_connection();
+        let author = signup_user(&conn, "favorited-by-target");
+        let article = create_article(&conn, author.id, "favorited-by-target", &[]);
+        let target = signup_user(&conn, "favorite-target");
+        Favorite::create(
+            &conn,
+            &CreateFavorite {
+                user_id: target.id,
+                article_id: article.id,
+            },
+        )
+        .unwrap();
+        let viewer = author;
+
+        let (list, count) =
+            list_favorited_by(&conn, &target.username, Some(&viewer), 20, 0).unwrap();
+
+        assert_eq!(count, 1);
+        let listed = &list[0];
+        assert_eq!(listed.article.id, article.id);
+        assert!(!listed.favorited);
+        assert_eq!(listed.favorites_count, 1);
+    }
 }
@@ -12,21 +12,56 @@ use actix_web::{web, HttpRequest, HttpResponse};
 type ArticleIdSlug = String;
 type CommentIdSlug = String;
 
-pub async fn index(state: web::Data<AppState>, req: HttpRequest) -> ApiResponse {
-    let conn = state.get_conn()?;
+#[utoipa::path(
+    get,
+    path = "/api/articles/{article_title_slug}/comments",
+    tag = "comment",
+    params(("article_title_slug" = String, Path, description = "Slug of the article")),
+    responses(
+        (status = 200, description = "Comments of the article, newest first", body = MultipleCommentsResponse),
+        (status = 404, description = "Article not found", body = crate::middleware::error::ErrorResponse),
+    ),
+)]
+pub async fn index(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<ArticleIdSlug>,
+) -> ApiResponse {
+    let conn = state.read_conn()?;
     let current_user = auth::get_current_user(&req).ok();
-    let list = service::fetch_comments_list(&conn, &current_user)?;
+    let article_title_slug = path.into_inner();
+    let list = state.timed(&req, "comment.list", || {
+        service::fetch_comments_list(
+            &conn,
+            &service::FetchCommentsList {
+                article_title_slug,
+                current_user,
+            },
+        )
+    })?;
     let res = MultipleCommentsResponse::from(list);
     Ok(HttpResponse::Ok().json(res))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/articles/{article_title_slug}/comments",
+    tag = "comment",
+    params(("article_title_slug" = String, Path, description = "Slug of the article")),
+    request_body = request::CreateCommentRequest,
+    responses(
+        (status = 200, description = "Created comment", body = SingleCommentResponse),
+        (status = 404, description = "Article not found", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
 pub async fn create(
     state: web::Data<AppState>,
     req: HttpRequest,
     path: web::Path<ArticleIdSlug>,
     form: web::Json<request::CreateCommentRequest>,
 ) -> ApiResponse {
-    let conn = state.get_conn()?;
+    let conn = state.write_conn()?;
     let current_user = auth::get_current_user(&req)?;
     let article_title_slug = path.into_inner();
     let (comment, profile) = service::create(
@@ -41,12 +76,26 @@ pub async fn create(
     Ok(HttpResponse::Ok().json(res))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/articles/{article_title_slug}/comments/{comment_id}",
+    tag = "comment",
+    params(
+        ("article_title_slug" = String, Path, description = "Slug of the article"),
+        ("comment_id" = String, Path, description = "Id of the comment"),
+    ),
+    responses(
+        (status = 200, description = "Comment deleted"),
+        (status = 404, description = "Comment not found", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
 pub async fn delete(
     state: web::Data<AppState>,
     req: HttpRequest,
     path: web::Path<(ArticleIdSlug, CommentIdSlug)>,
 ) -> ApiResponse {
-    let conn = state.get_conn()?;
+    let conn = state.write_conn()?;
     let current_user = auth::get_current_user(&req)?;
     let (article_title_slug, comment_id) = path.into_inner();
     let comment_id = uuid::parse(&comment_id)?;
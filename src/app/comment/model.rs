@@ -1,11 +1,15 @@
 use crate::app::article::model::Article;
 use crate::app::user::model::User;
 use crate::error::AppError;
+use crate::middleware::error::ErrorResponse;
 use crate::schema::comments;
-use chrono::NaiveDateTime;
+use chrono::{DateTime, Utc};
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(Identifiable, Deserialize, Serialize, Queryable, Associations, Debug, Clone)]
@@ -17,18 +21,64 @@ pub struct Comment {
     pub article_id: Uuid,
     pub author_id: Uuid,
     pub body: String,
-    pub created_at: NaiveDateTime,
-    pub updated_at: NaiveDateTime,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// Callers resolve the article first, so this only catches one (hard) deleted in between.
+fn article_missing_error(err: DieselError) -> AppError {
+    match &err {
+        DieselError::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, info)
+            if info.constraint_name() == Some("comments_article_id_fkey") =>
+        {
+            AppError::NotFound(json!(ErrorResponse::from("article was not found")))
+        }
+        _ => err.into(),
+    }
 }
 
 impl Comment {
     pub fn create(conn: &PgConnection, record: &CreateComment) -> Result<Self, AppError> {
         let new_comment = diesel::insert_into(comments::table)
             .values(record)
-            .get_result::<Comment>(conn)?;
+            .get_result::<Comment>(conn)
+            .map_err(article_missing_error)?;
         Ok(new_comment)
     }
 
+    // Newest first, the id breaking ties between comments created at the same instant.
+    pub fn fetch_by_article_id(
+        conn: &PgConnection,
+        article_id: &Uuid,
+    ) -> Result<Vec<Self>, AppError> {
+        let list = comments::table
+            .filter(comments::article_id.eq(article_id))
+            .order((comments::created_at.desc(), comments::id.desc()))
+            .load::<Self>(conn)?;
+        Ok(list)
+    }
+
+    pub fn count_by_article_id(conn: &PgConnection, article_id: &Uuid) -> Result<i64, AppError> {
+        let count = comments::table
+            .filter(comments::article_id.eq(article_id))
+            .count()
+            .get_result::<i64>(conn)?;
+        Ok(count)
+    }
+
+    // A single grouped query for lists; articles without comments are absent from the map.
+    pub fn count_by_article_ids(
+        conn: &PgConnection,
+        article_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, i64>, AppError> {
+        let counts = comments::table
+            .filter(comments::article_id.eq_any(article_ids))
+            .group_by(comments::article_id)
+            .select((comments::article_id, diesel::dsl::count(comments::id)))
+            .load::<(Uuid, i64)>(conn)?;
+        Ok(counts.into_iter().collect())
+    }
+
     pub fn delete(conn: &PgConnection, params: &DeleteComment) -> Result<(), AppError> {
         let _ = diesel::delete(comments::table)
             .filter(comments::id.eq(params.comment_id))
@@ -52,3 +102,28 @@ pub struct CreateComment {
     pub author_id: Uuid,
     pub article_id: Uuid,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::db::establish_test_connection;
+    use crate::utils::test_support::signup_user;
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn create_on_missing_article_is_not_found() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "comment-missing-article");
+
+        let result = Comment::create(
+            &conn,
+            &CreateComment {
+                body: "lost comment".to_owned(),
+                author_id: user.id,
+                article_id: Uuid::new_v4(),
+            },
+        );
+
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+}
@@ -1,11 +1,12 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct CreateCommentRequest {
-    pub comment: InnerComment,
+    pub comment: CreateCommentInner,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct InnerComment {
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CreateCommentInner {
     pub body: String,
 }
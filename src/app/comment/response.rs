@@ -3,9 +3,10 @@ use crate::app::profile::model::Profile;
 use crate::utils::date::Iso8601;
 use serde::{Deserialize, Serialize};
 use std::convert::From;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct SingleCommentResponse {
     pub comment: InnerComment,
 }
@@ -29,7 +30,7 @@ impl From<(Comment, Profile)> for SingleCommentResponse {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct MultipleCommentsResponse {
     pub comments: Vec<InnerComment>,
 }
@@ -59,17 +60,19 @@ impl From<Vec<(Comment, Profile)>> for MultipleCommentsResponse {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct InnerComment {
     pub id: Uuid,
+    #[schema(value_type = String)]
     pub created_at: Iso8601,
+    #[schema(value_type = String)]
     pub updated_at: Iso8601,
     pub body: String,
     pub author: InnerAuthor,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct InnerAuthor {
     pub username: String,
     pub bio: Option<String>,
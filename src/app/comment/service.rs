@@ -1,10 +1,10 @@
 use super::model::{Comment, CreateComment, DeleteComment};
 use crate::app::article::model::{Article, FetchBySlugAndAuthorId};
 use crate::app::profile::model::Profile;
-use crate::app::profile::service::{conver_user_to_profile, ConverUserToProfile};
 use crate::app::user::model::User;
 use crate::error::AppError;
 use diesel::pg::PgConnection;
+use std::collections::HashSet;
 use uuid::Uuid;
 
 pub struct CreateCommentService {
@@ -21,13 +21,7 @@ pub fn create(
         article_title_slug,
         author,
     } = params;
-    let article = Article::fetch_by_slug_and_author_id(
-        conn,
-        &FetchBySlugAndAuthorId {
-            slug: article_title_slug.to_owned(),
-            author_id: author.id,
-        },
-    )?;
+    let article = Article::find_by_slug(conn, article_title_slug)?;
     let comment = Comment::create(
         conn,
         &CreateComment {
@@ -36,37 +30,50 @@ pub fn create(
             article_id: article.id.to_owned(),
         },
     )?;
-    let profile = author.fetch_profile(conn, &author.id)?;
+    let profile = Profile::from_user(author, false);
     Ok((comment, profile))
 }
 
+pub struct FetchCommentsList {
+    pub article_title_slug: String,
+    pub current_user: Option<User>,
+}
+// Newest comment first. Authors and whether they are followed take one query each, however
+// many comments there are.
 pub fn fetch_comments_list(
     conn: &PgConnection,
-    current_user: &Option<User>,
+    params: &FetchCommentsList,
 ) -> Result<Vec<(Comment, Profile)>, AppError> {
-    let comments = {
-        use crate::schema::comments;
-        // use crate::schema::comments::dsl::*;
-        use crate::schema::users;
-        use diesel::prelude::*;
-        comments::table
-            .inner_join(users::table)
-            // .filter(comments::article_id.eq(article_id))
-            .get_results::<(Comment, User)>(conn)?
-    };
+    let article = Article::find_by_slug(conn, &params.article_title_slug)?;
+    let comments = Comment::fetch_by_article_id(conn, &article.id)?;
 
-    let comments = comments
-        .iter()
-        .map(|(comment, user)| {
-            // TODO: avoid N+1. Write one query to fetch all data somehow.
-            let profile = conver_user_to_profile(conn, &ConverUserToProfile { user, current_user });
+    let author_ids = distinct_author_ids(&comments);
+    let authors = User::find_by_ids(conn, &author_ids)?;
+    let followee_ids = match &params.current_user {
+        Some(current_user) => User::followees_among(conn, &current_user.id, &author_ids)?,
+        None => HashSet::new(),
+    };
 
-            // TODO: avoid copy
-            (comment.to_owned(), profile)
+    comments
+        .into_iter()
+        .map(|comment| {
+            // Comments reference their author, so a missing one is a bug rather than a 404.
+            let author = authors
+                .get(&comment.author_id)
+                .ok_or(AppError::InternalServerError)?;
+            let profile = Profile::from_user(author, followee_ids.contains(&author.id));
+            Ok((comment, profile))
         })
-        .collect::<Vec<(Comment, Profile)>>();
+        .collect()
+}
 
-    Ok(comments)
+fn distinct_author_ids(comments: &[Comment]) -> Vec<Uuid> {
+    let mut seen = HashSet::new();
+    comments
+        .iter()
+        .map(|comment| comment.author_id)
+        .filter(|author_id| seen.insert(*author_id))
+        .collect()
 }
 
 pub struct DeleteCommentService {
@@ -93,3 +100,102 @@ pub fn delete_comment(conn: &PgConnection, params: &DeleteCommentService) -> Res
     )?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::follow::model::{CreateFollow, Follow};
+    use crate::schema::comments;
+    use crate::utils::db::establish_test_connection;
+    use crate::utils::test_support::{create_article, signup_user};
+    use chrono::{Duration, Utc};
+    use diesel::prelude::*;
+
+    fn comment_by(author_id: Uuid) -> Comment {
+        Comment {
+            id: Uuid::new_v4(),
+            article_id: Uuid::new_v4(),
+            author_id,
+            body: "body".to_owned(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn distinct_author_ids_keeps_each_author_once() {
+        let (first, second, third) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let comments = [first, second, first, third, second]
+            .into_iter()
+            .map(comment_by)
+            .collect::<Vec<_>>();
+
+        assert_eq!(distinct_author_ids(&comments), vec![first, second, third]);
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn fetch_comments_list_is_newest_first_with_each_author() {
+        let conn = establish_test_connection();
+        let author = signup_user(&conn, "comments-list-author");
+        let followed = signup_user(&conn, "comments-list-followed");
+        let reader = signup_user(&conn, "comments-list-reader");
+        Follow::create(
+            &conn,
+            &CreateFollow {
+                follower_id: reader.id,
+                followee_id: followed.id,
+            },
+        )
+        .unwrap();
+        let article = create_article(&conn, author.id, "comments-list-article", &[]);
+        let commenters = [&followed, &reader, &followed, &author];
+        let now = Utc::now();
+        let mut oldest_first = vec![];
+        for (i, commenter) in commenters.iter().enumerate() {
+            let comment = Comment::create(
+                &conn,
+                &CreateComment {
+                    body: format!("comment {}", i),
+                    author_id: commenter.id,
+                    article_id: article.id,
+                },
+            )
+            .unwrap();
+            // Everything in the test transaction shares one `now()`, so spread them out.
+            diesel::update(comments::table.find(comment.id))
+                .set(comments::created_at.eq(now - Duration::minutes(10 - i as i64)))
+                .execute(&conn)
+                .unwrap();
+            oldest_first.push(comment.id);
+        }
+
+        let list = fetch_comments_list(
+            &conn,
+            &FetchCommentsList {
+                article_title_slug: article.slug,
+                current_user: Some(reader),
+            },
+        )
+        .unwrap();
+
+        let ids = list
+            .iter()
+            .map(|(comment, _)| comment.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, oldest_first.into_iter().rev().collect::<Vec<_>>());
+        let authors = list
+            .iter()
+            .map(|(_, profile)| (profile.username.as_str(), profile.following))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            authors,
+            vec![
+                ("comments-list-author", false),
+                ("comments-list-followed", true),
+                ("comments-list-reader", false),
+                ("comments-list-followed", true),
+            ]
+        );
+    }
+}
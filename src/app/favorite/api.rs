@@ -9,40 +9,64 @@ use actix_web::{web, HttpRequest, HttpResponse};
 
 type ArticleIdSlug = String;
 
+#[utoipa::path(
+    post,
+    path = "/api/articles/{article_title_slug}/favorite",
+    tag = "favorite",
+    params(("article_title_slug" = String, Path, description = "Slug of the article")),
+    responses(
+        (status = 200, description = "Favorited article", body = SingleArticleResponse),
+        (status = 404, description = "Article not found", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
 pub async fn favorite(
     state: web::Data<AppState>,
     req: HttpRequest,
     path: web::Path<ArticleIdSlug>,
 ) -> ApiResponse {
-    let conn = state.get_conn()?;
+    let conn = state.write_conn()?;
     let current_user = auth::get_current_user(&req)?;
     let article_title_slug = path.into_inner();
-    let (article, profile, favorite_info, tags_list) = service::favorite(
+    let (article, profile, favorite_info, tags_list, comments_count) = service::favorite(
         &conn,
         &service::FavoriteService {
             current_user,
             article_title_slug,
         },
     )?;
-    let res = SingleArticleResponse::from((article, profile, favorite_info, tags_list));
+    let res =
+        SingleArticleResponse::from((article, profile, favorite_info, tags_list, comments_count));
     Ok(HttpResponse::Ok().json(res))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/articles/{article_title_slug}/favorite",
+    tag = "favorite",
+    params(("article_title_slug" = String, Path, description = "Slug of the article")),
+    responses(
+        (status = 200, description = "Unfavorited article", body = SingleArticleResponse),
+        (status = 404, description = "Article not found", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
 pub async fn unfavorite(
     state: web::Data<AppState>,
     req: HttpRequest,
     path: web::Path<ArticleIdSlug>,
 ) -> ApiResponse {
-    let conn = state.get_conn()?;
+    let conn = state.write_conn()?;
     let current_user = auth::get_current_user(&req)?;
     let article_title_slug = path.into_inner();
-    let (article, profile, favorite_info, tags_list) = service::unfavorite(
+    let (article, profile, favorite_info, tags_list, comments_count) = service::unfavorite(
         &conn,
         &UnfavoriteService {
             current_user,
             article_title_slug,
         },
     )?;
-    let res = SingleArticleResponse::from((article, profile, favorite_info, tags_list));
+    let res =
+        SingleArticleResponse::from((article, profile, favorite_info, tags_list, comments_count));
     Ok(HttpResponse::Ok().json(res))
 }
@@ -2,27 +2,30 @@ use crate::app::article::model::Article;
 use crate::app::user::model::User;
 use crate::error::AppError;
 use crate::schema::favorites;
-use chrono::NaiveDateTime;
+use chrono::{DateTime, Utc};
 use diesel::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, Queryable, Identifiable, Associations, Clone, Debug)]
 #[belongs_to(Article, foreign_key = "article_id")]
 #[belongs_to(User, foreign_key = "user_id")]
 #[table_name = "favorites"]
+#[primary_key(user_id, article_id)]
 pub struct Favorite {
-    pub id: Uuid,
     pub article_id: Uuid,
     pub user_id: Uuid,
-    pub created_at: NaiveDateTime,
-    pub updated_at: NaiveDateTime,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
 impl Favorite {
+    // Returns affected rows, so favoriting twice is a no-op returning 0.
     pub fn create(conn: &PgConnection, record: &CreateFavorite) -> Result<usize, AppError> {
         let item = diesel::insert_into(favorites::table)
             .values(record)
+            .on_conflict_do_nothing()
             .execute(conn)?;
         Ok(item)
     }
@@ -41,6 +44,22 @@ impl Favorite {
         Ok(item)
     }
 
+    // Articles without favorites are missing from the map rather than mapped to 0.
+    pub fn count_by_article_ids(
+        conn: &PgConnection,
+        article_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, i64>, AppError> {
+        let counts = favorites::table
+            .filter(favorites::article_id.eq_any(article_ids))
+            .group_by(favorites::article_id)
+            .select((
+                favorites::article_id,
+                diesel::dsl::count(favorites::user_id),
+            ))
+            .load::<(Uuid, i64)>(conn)?;
+        Ok(counts.into_iter().collect())
+    }
+
     pub fn fetch_favorited_article_ids_by_username(
         conn: &PgConnection,
         username: &str,
@@ -1,5 +1,5 @@
-use crate::app::article::model::{Article, FetchBySlugAndAuthorId};
-use crate::app::article::service::{fetch_article, FetchArticle};
+use crate::app::article::model::Article;
+use crate::app::article::service::{fetch_article, CommentsCount, FetchArticle};
 use crate::app::favorite::model::{CreateFavorite, DeleteFavorite, Favorite, FavoriteInfo};
 use crate::app::profile::model::Profile;
 use crate::app::tag::model::Tag;
@@ -16,14 +16,8 @@ pub struct FavoriteService {
 pub fn favorite(
     conn: &PgConnection,
     params: &FavoriteService,
-) -> Result<(Article, Profile, FavoriteInfo, Vec<Tag>), AppError> {
-    let article = Article::fetch_by_slug_and_author_id(
-        conn,
-        &FetchBySlugAndAuthorId {
-            slug: params.article_title_slug.to_owned(),
-            author_id: params.current_user.id,
-        },
-    )?;
+) -> Result<(Article, Profile, FavoriteInfo, Vec<Tag>, CommentsCount), AppError> {
+    let article = Article::find_by_slug(conn, &params.article_title_slug)?;
     let _ = Favorite::create(
         conn,
         &CreateFavorite {
@@ -49,14 +43,8 @@ pub struct UnfavoriteService {
 pub fn unfavorite(
     conn: &PgConnection,
     params: &UnfavoriteService,
-) -> Result<(Article, Profile, FavoriteInfo, Vec<Tag>), AppError> {
-    let article = Article::fetch_by_slug_and_author_id(
-        conn,
-        &FetchBySlugAndAuthorId {
-            slug: params.article_title_slug.to_owned(),
-            author_id: params.current_user.id,
-        },
-    )?;
+) -> Result<(Article, Profile, FavoriteInfo, Vec<Tag>, CommentsCount), AppError> {
+    let article = Article::find_by_slug(conn, &params.article_title_slug)?;
     let _ = Favorite::delete(
         conn,
         &DeleteFavorite {
@@ -73,3 +61,75 @@ pub fn unfavorite(
     )?;
     Ok(item)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::db::establish_test_connection;
+    use crate::utils::test_support::{create_article, signup_user};
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn favorite_twice_is_idempotent() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "favorite");
+        let article = create_article(&conn, user.id, "favorite-test", &[]);
+        let params = FavoriteService {
+            current_user: user,
+            article_title_slug: article.slug,
+        };
+
+        let (_, _, first, _, _) = favorite(&conn, &params).unwrap();
+        let (_, _, second, _, _) = favorite(&conn, &params).unwrap();
+
+        assert!(first.is_favorited);
+        assert_eq!(first.favorites_count, 1);
+        assert!(second.is_favorited);
+        assert_eq!(second.favorites_count, 1);
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn unfavorite_without_prior_favorite() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "favorite");
+        let article = create_article(&conn, user.id, "favorite-test", &[]);
+        let params = UnfavoriteService {
+            current_user: user,
+            article_title_slug: article.slug,
+        };
+
+        let (_, _, favorite_info, _, _) = unfavorite(&conn, &params).unwrap();
+
+        assert!(!favorite_info.is_favorited);
+        assert_eq!(favorite_info.favorites_count, 0);
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn unfavorite_after_favorite_decrements_count() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "favorite");
+        let article = create_article(&conn, user.id, "favorite-test", &[]);
+        let _ = favorite(
+            &conn,
+            &FavoriteService {
+                current_user: user.clone(),
+                article_title_slug: article.slug.clone(),
+            },
+        )
+        .unwrap();
+
+        let (_, _, favorite_info, _, _) = unfavorite(
+            &conn,
+            &UnfavoriteService {
+                current_user: user,
+                article_title_slug: article.slug,
+            },
+        )
+        .unwrap();
+
+        assert!(!favorite_info.is_favorited);
+        assert_eq!(favorite_info.favorites_count, 0);
+    }
+}
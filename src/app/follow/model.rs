@@ -1,7 +1,7 @@
 use crate::app::user::model::User;
 use crate::error::AppError;
 use crate::schema::follows;
-use chrono::NaiveDateTime;
+use chrono::{DateTime, Utc};
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -13,14 +13,16 @@ use uuid::Uuid;
 pub struct Follow {
     pub followee_id: Uuid,
     pub follower_id: Uuid,
-    pub created_at: NaiveDateTime,
-    pub updated_at: NaiveDateTime,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
 impl Follow {
+    // Following someone already followed is a no-op rather than a unique violation.
     pub fn create(conn: &PgConnection, params: &CreateFollow) -> Result<(), AppError> {
         let _ = diesel::insert_into(follows::table)
             .values(params)
+            .on_conflict_do_nothing()
             .execute(conn)?;
         Ok(())
     }
@@ -35,6 +37,19 @@ impl Follow {
         Ok(())
     }
 
+    pub fn find(
+        conn: &PgConnection,
+        follower_id: &Uuid,
+        followee_id: &Uuid,
+    ) -> Result<Option<Self>, AppError> {
+        let follow = follows::table
+            .filter(follows::follower_id.eq(follower_id))
+            .filter(follows::followee_id.eq(followee_id))
+            .first::<Self>(conn)
+            .optional()?;
+        Ok(follow)
+    }
+
     pub fn fetch_folowee_ids_by_follower_id(
         conn: &PgConnection,
         follower_id: &Uuid,
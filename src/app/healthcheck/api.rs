@@ -1,5 +1,13 @@
 use actix_web::{HttpResponse, Responder};
 
+#[utoipa::path(
+    get,
+    path = "/api/healthcheck",
+    tag = "healthcheck",
+    responses(
+        (status = 200, description = "Server is healthy", body = String),
+    ),
+)]
 pub async fn index() -> impl Responder {
     HttpResponse::Ok().body("OK")
 }
@@ -0,0 +1,134 @@
+use crate::app::user::model::User;
+use crate::constants;
+use crate::error::AppError;
+use crate::middleware::error::ErrorResponse;
+use crate::schema::idempotency_keys;
+use chrono::{DateTime, Duration, Utc};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use serde_json::{json, Value as JsonValue};
+use uuid::Uuid;
+
+#[derive(Queryable, Associations, Clone)]
+#[belongs_to(User, foreign_key = "user_id")]
+#[table_name = "idempotency_keys"]
+pub struct IdempotencyKey {
+    pub user_id: Uuid,
+    pub key: String,
+    // `null` until the request that claimed the key has finished.
+    pub response: Option<JsonValue>,
+    pub created_at: DateTime<Utc>,
+    // `null` for keys claimed before requests were hashed.
+    pub request_hash: Option<String>,
+}
+
+impl IdempotencyKey {
+    // Claims `key` for the user, or returns false when it is already taken. Meant to run in the
+    // transaction of the work it guards, so that a concurrent claim of the same key waits for it
+    // to finish. Keys older than `IDEMPOTENCY_KEY_TTL_HOURS` can be claimed again.
+    pub fn claim(
+        conn: &PgConnection,
+        user_id: &Uuid,
+        key: &str,
+        request_hash: &str,
+    ) -> Result<bool, AppError> {
+        let expired_at = Utc::now() - Duration::hours(constants::IDEMPOTENCY_KEY_TTL_HOURS);
+        diesel::delete(
+            idempotency_keys::table
+                .filter(idempotency_keys::user_id.eq(user_id))
+                .filter(idempotency_keys::key.eq(key))
+                .filter(idempotency_keys::created_at.lt(expired_at)),
+        )
+        .execute(conn)?;
+        let inserted = diesel::insert_into(idempotency_keys::table)
+            .values((
+                idempotency_keys::user_id.eq(user_id),
+                idempotency_keys::key.eq(key),
+                idempotency_keys::request_hash.eq(request_hash),
+            ))
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+        Ok(inserted == 1)
+    }
+
+    pub fn save_response(
+        conn: &PgConnection,
+        user_id: &Uuid,
+        key: &str,
+        response: &JsonValue,
+    ) -> Result<(), AppError> {
+        diesel::update(
+            idempotency_keys::table
+                .filter(idempotency_keys::user_id.eq(user_id))
+                .filter(idempotency_keys::key.eq(key)),
+        )
+        .set(idempotency_keys::response.eq(response))
+        .execute(conn)?;
+        Ok(())
+    }
+
+    // The saved response of `key`, unless it was claimed by a request other than `request_hash`.
+    pub fn find_response(
+        conn: &PgConnection,
+        user_id: &Uuid,
+        key: &str,
+        request_hash: &str,
+    ) -> Result<Option<JsonValue>, AppError> {
+        let found = idempotency_keys::table
+            .filter(idempotency_keys::user_id.eq(user_id))
+            .filter(idempotency_keys::key.eq(key))
+            .select((idempotency_keys::request_hash, idempotency_keys::response))
+            .first::<(Option<String>, Option<JsonValue>)>(conn)
+            .optional()?;
+        match found {
+            Some((Some(claimed_by), _)) if claimed_by != request_hash => {
+                Err(AppError::UnprocessableEntity(json!(ErrorResponse::from(
+                    "idempotency key was already used with a different request"
+                ))))
+            }
+            Some((_, response)) => Ok(response),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::db::establish_test_connection;
+    use crate::utils::test_support::signup_user;
+    use serde_json::json;
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn claimed_key_answers_with_saved_response() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "idempotency-key");
+
+        assert!(IdempotencyKey::claim(&conn, &user.id, "publish", "hash").unwrap());
+        assert_eq!(
+            IdempotencyKey::find_response(&conn, &user.id, "publish", "hash").unwrap(),
+            None
+        );
+        IdempotencyKey::save_response(&conn, &user.id, "publish", &json!({"ok": true})).unwrap();
+
+        assert!(!IdempotencyKey::claim(&conn, &user.id, "publish", "hash").unwrap());
+        assert_eq!(
+            IdempotencyKey::find_response(&conn, &user.id, "publish", "hash").unwrap(),
+            Some(json!({"ok": true}))
+        );
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn key_reused_for_another_request_is_unprocessable() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "idempotency-reused");
+        IdempotencyKey::claim(&conn, &user.id, "publish", "hash").unwrap();
+        IdempotencyKey::save_response(&conn, &user.id, "publish", &json!({"ok": true})).unwrap();
+
+        let result = IdempotencyKey::find_response(&conn, &user.id, "publish", "other-hash");
+
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
+    }
+}
@@ -0,0 +1,31 @@
+use crate::error::AppError;
+use crate::middleware::error::ErrorResponse;
+use crate::middleware::state::AppState;
+use crate::utils::api::ApiResponse;
+use actix_web::{web, HttpResponse};
+use serde_json::json;
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "metrics",
+    responses(
+        (status = 200, description = "Metrics in the Prometheus text format", body = String),
+        (status = 404, description = "Metrics are disabled", body = crate::middleware::error::ErrorResponse),
+    ),
+)]
+pub async fn index(state: web::Data<AppState>) -> ApiResponse {
+    if !state.config.metrics_enabled {
+        return Err(AppError::NotFound(json!(ErrorResponse::from(
+            "metrics are disabled"
+        ))));
+    }
+    state.observe_pools();
+    let body = state.metrics.render().map_err(|err| {
+        error!("Cannot render metrics: {}", err);
+        AppError::InternalServerError
+    })?;
+    Ok(HttpResponse::Ok()
+        .content_type(prometheus::TEXT_FORMAT)
+        .body(body))
+}
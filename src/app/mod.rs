@@ -1,8 +1,12 @@
+pub mod admin;
 pub mod article;
 pub mod comment;
 pub mod favorite;
 pub mod follow;
+pub mod healthcheck;
+pub mod idempotency;
+pub mod metrics;
+pub mod openapi;
 pub mod profile;
 pub mod tag;
 pub mod user;
-pub mod healthcheck;
\ No newline at end of file
@@ -0,0 +1,7 @@
+use super::doc::ApiDoc;
+use actix_web::{HttpResponse, Responder};
+use utoipa::OpenApi;
+
+pub async fn index() -> impl Responder {
+    HttpResponse::Ok().json(ApiDoc::openapi())
+}
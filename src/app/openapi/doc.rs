@@ -0,0 +1,101 @@
+use crate::app::{admin, article, comment, favorite, healthcheck, metrics, profile, tag, user};
+use crate::constants;
+use crate::middleware::error;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        healthcheck::api::index,
+        metrics::api::index,
+        admin::api::read_only,
+        tag::api::index,
+        tag::api::popular,
+        user::api::signin,
+        user::api::signup,
+        user::api::me,
+        user::api::refresh,
+        user::api::update,
+        user::api::delete,
+        user::api::upload_image,
+        profile::api::show,
+        profile::api::follow,
+        profile::api::follow_many,
+        profile::api::unfollow,
+        profile::api::follow_status,
+        profile::api::followers,
+        profile::api::following,
+        article::api::index,
+        article::api::feed,
+        article::api::batch,
+        article::api::index_by_author,
+        article::api::index_by_tag,
+        article::api::show,
+        article::api::create,
+        article::api::validate,
+        article::api::update,
+        article::api::delete,
+        article::api::bulk_delete,
+        favorite::api::favorite,
+        favorite::api::unfavorite,
+        comment::api::index,
+        comment::api::create,
+        comment::api::delete,
+    ),
+    components(schemas(
+        error::ErrorResponse,
+        admin::request::ReadOnlyRequest,
+        admin::response::ReadOnlyResponse,
+        tag::response::TagsResponse,
+        tag::response::PopularTag,
+        user::request::Signin,
+        user::request::SigninUser,
+        user::request::Signup,
+        user::request::SignupUser,
+        user::request::Update,
+        user::request::UpdateUser,
+        user::response::UserResponse,
+        user::response::AuthUser,
+        profile::response::ProfileResponse,
+        profile::response::ProfileContent,
+        profile::response::MultipleProfilesResponse,
+        profile::request::FollowProfilesRequest,
+        profile::response::FollowProfilesResponse,
+        profile::response::FollowStatusResponse,
+        article::request::CreateArticleRequest,
+        article::request::CreateArticleInner,
+        article::request::UpdateArticleRequest,
+        article::request::UpdateArticleInner,
+        article::request::BatchArticlesRequest,
+        article::request::BulkDeleteArticlesRequest,
+        article::response::BulkDeleteArticlesResponse,
+        article::response::SingleArticleResponse,
+        article::response::ArticleDraftResponse,
+        article::response::MultipleArticlesResponse,
+        article::response::ArticleContent,
+        article::response::AuthorContent,
+        comment::request::CreateCommentRequest,
+        comment::request::CreateCommentInner,
+        comment::response::SingleCommentResponse,
+        comment::response::MultipleCommentsResponse,
+        comment::response::InnerComment,
+        comment::response::InnerAuthor,
+    )),
+    modifiers(&SecurityAddon),
+)]
+pub struct ApiDoc;
+
+// Authenticated endpoints expect `Authorization: Token <jwt>`.
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "token",
+                SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(constants::AUTHORIZATION))),
+            );
+        }
+    }
+}
@@ -1,17 +1,32 @@
-use super::response::ProfileResponse;
-use super::service;
+use super::response::{
+    FollowProfilesResponse, FollowStatusResponse, MultipleProfilesResponse, ProfileResponse,
+};
+use super::{request, service};
+use crate::constants::header;
 use crate::middleware::{auth, state::AppState};
 use crate::utils::api::ApiResponse;
+use crate::utils::pagination::{self, Pagination};
 use actix_web::{web, HttpRequest, HttpResponse};
 
 type UsernameSlug = String;
 
+#[utoipa::path(
+    get,
+    path = "/api/profiles/{username}",
+    tag = "profile",
+    params(("username" = String, Path, description = "Username of the profile")),
+    responses(
+        (status = 200, description = "Profile", body = ProfileResponse),
+        (status = 404, description = "Profile not found", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
 pub async fn show(
     state: web::Data<AppState>,
     req: HttpRequest,
     path: web::Path<UsernameSlug>,
 ) -> ApiResponse {
-    let conn = state.get_conn()?;
+    let conn = state.read_conn()?;
     let current_user = auth::get_current_user(&req)?;
     let _username = path.into_inner();
     let profile = service::fetch_by_name(
@@ -25,12 +40,23 @@ pub async fn show(
     Ok(HttpResponse::Ok().json(res))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/profiles/{username}/follow",
+    tag = "profile",
+    params(("username" = String, Path, description = "Username of the profile")),
+    responses(
+        (status = 200, description = "Followed profile", body = ProfileResponse),
+        (status = 404, description = "Profile not found", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
 pub async fn follow(
     state: web::Data<AppState>,
     req: HttpRequest,
     path: web::Path<UsernameSlug>,
 ) -> ApiResponse {
-    let conn = state.get_conn()?;
+    let conn = state.write_conn()?;
     let current_user = auth::get_current_user(&req)?;
     let username = path.into_inner();
     let profile = current_user.follow(&conn, &username)?;
@@ -38,15 +64,146 @@ pub async fn follow(
     Ok(HttpResponse::Ok().json(res))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/profiles/follow",
+    tag = "profile",
+    request_body = request::FollowProfilesRequest,
+    responses(
+        (status = 200, description = "Followed profiles and unknown usernames", body = FollowProfilesResponse),
+        (status = 401, description = "Unauthorized", body = crate::middleware::error::ErrorResponse),
+        (status = 422, description = "Too many usernames", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
+pub async fn follow_many(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Json<request::FollowProfilesRequest>,
+) -> ApiResponse {
+    form.validate()?;
+    let conn = state.write_conn()?;
+    let current_user = auth::get_current_user(&req)?;
+    let (profiles, not_found) = service::follow_many(&conn, &current_user, &form.usernames)?;
+    let res = FollowProfilesResponse::from((profiles, not_found));
+    Ok(HttpResponse::Ok().json(res))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/profiles/{username}/follow",
+    tag = "profile",
+    params(("username" = String, Path, description = "Username of the profile")),
+    responses(
+        (status = 200, description = "Unfollowed profile", body = ProfileResponse),
+        (status = 404, description = "Profile not found", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
 pub async fn unfollow(
     state: web::Data<AppState>,
     req: HttpRequest,
     path: web::Path<UsernameSlug>,
 ) -> ApiResponse {
-    let conn = state.get_conn()?;
+    let conn = state.write_conn()?;
     let current_user = auth::get_current_user(&req)?;
     let username = path.into_inner();
     let profile = current_user.unfollow(&conn, &username)?;
     let res = ProfileResponse::from(profile);
     Ok(HttpResponse::Ok().json(res))
 }
+
+#[utoipa::path(
+    get,
+    path = "/api/profiles/{username}/follow-status",
+    tag = "profile",
+    params(("username" = String, Path, description = "Username of the profile")),
+    responses(
+        (status = 200, description = "Whether and since when the user is followed", body = FollowStatusResponse),
+        (status = 401, description = "Unauthorized", body = crate::middleware::error::ErrorResponse),
+        (status = 404, description = "Profile not found", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
+pub async fn follow_status(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<UsernameSlug>,
+) -> ApiResponse {
+    let conn = state.read_conn()?;
+    let current_user = auth::get_current_user(&req)?;
+    let username = path.into_inner();
+    let since = service::fetch_follow_since(&conn, &current_user, &username)?;
+    let res = FollowStatusResponse::from(since);
+    Ok(HttpResponse::Ok().json(res))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/profiles/{username}/followers",
+    tag = "profile",
+    params(
+        ("username" = String, Path, description = "Username of the profile"),
+        pagination::PaginationQuery,
+    ),
+    responses(
+        (status = 200, description = "Profiles following the user", body = MultipleProfilesResponse),
+        (status = 400, description = "Invalid pagination", body = crate::middleware::error::ErrorResponse),
+        (status = 404, description = "Profile not found", body = crate::middleware::error::ErrorResponse),
+    ),
+)]
+pub async fn followers(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<UsernameSlug>,
+    pagination: Pagination,
+) -> ApiResponse {
+    let conn = state.read_conn()?;
+    let current_user = auth::get_current_user(&req).ok();
+    let username = path.into_inner();
+    let Pagination { limit, offset } = pagination;
+    let (profiles, profiles_count) =
+        service::list_followers(&conn, &username, current_user.as_ref(), limit, offset)?;
+
+    let res = MultipleProfilesResponse::from((profiles, profiles_count));
+    Ok(HttpResponse::Ok()
+        .insert_header((header::X_TOTAL_COUNT, profiles_count.to_string()))
+        .insert_header((header::X_LIMIT, limit.to_string()))
+        .insert_header((header::X_OFFSET, offset.to_string()))
+        .json(res))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/profiles/{username}/following",
+    tag = "profile",
+    params(
+        ("username" = String, Path, description = "Username of the profile"),
+        pagination::PaginationQuery,
+    ),
+    responses(
+        (status = 200, description = "Profiles the user follows", body = MultipleProfilesResponse),
+        (status = 400, description = "Invalid pagination", body = crate::middleware::error::ErrorResponse),
+        (status = 404, description = "Profile not found", body = crate::middleware::error::ErrorResponse),
+    ),
+)]
+pub async fn following(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<UsernameSlug>,
+    pagination: Pagination,
+) -> ApiResponse {
+    let conn = state.read_conn()?;
+    let current_user = auth::get_current_user(&req).ok();
+    let username = path.into_inner();
+    let Pagination { limit, offset } = pagination;
+    let (profiles, profiles_count) =
+        service::list_following(&conn, &username, current_user.as_ref(), limit, offset)?;
+
+    let res = MultipleProfilesResponse::from((profiles, profiles_count));
+    Ok(HttpResponse::Ok()
+        .insert_header((header::X_TOTAL_COUNT, profiles_count.to_string()))
+        .insert_header((header::X_LIMIT, limit.to_string()))
+        .insert_header((header::X_OFFSET, offset.to_string()))
+        .json(res))
+}
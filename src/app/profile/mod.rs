@@ -1,4 +1,5 @@
 pub mod api;
 pub mod model;
+pub mod request;
 pub mod response;
 pub mod service;
@@ -1,3 +1,4 @@
+use crate::app::user::model::User;
 use serde::{Deserialize, Serialize};
 
 #[derive(Queryable, Serialize, Deserialize, Debug, Clone)]
@@ -7,3 +8,58 @@ pub struct Profile {
     pub image: Option<String>,
     pub following: bool,
 }
+
+impl Profile {
+    // The only place a `User` becomes a `Profile`, so email and password never end up in one.
+    pub fn from_user(user: &User, following: bool) -> Self {
+        Self {
+            username: user.username.to_owned(),
+            bio: user.bio.to_owned(),
+            image: user.image.to_owned(),
+            following,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn user() -> User {
+        let now = Utc::now();
+        User {
+            id: Uuid::new_v4(),
+            email: "jake@example.com".to_owned(),
+            username: "jake".to_owned(),
+            password: "$2b$04$secret".to_owned(),
+            bio: Some("I work at statefarm".to_owned()),
+            image: None,
+            created_at: now,
+            updated_at: now,
+            last_login_at: Some(now),
+        }
+    }
+
+    #[test]
+    fn from_user_sets_following() {
+        assert!(Profile::from_user(&user(), true).following);
+        assert!(!Profile::from_user(&user(), false).following);
+    }
+
+    #[test]
+    fn from_user_omits_sensitive_fields() {
+        let profile = serde_json::to_value(Profile::from_user(&user(), false)).unwrap();
+        assert_eq!(
+            profile,
+            json!({
+                "username": "jake",
+                "bio": "I work at statefarm",
+                "image": null,
+                "following": false,
+            })
+        );
+    }
+}
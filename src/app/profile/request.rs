@@ -0,0 +1,45 @@
+use crate::error::AppError;
+use crate::utils::validator::ValidationErrors;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+const MAX_BATCH_USERNAMES: usize = 50;
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct FollowProfilesRequest {
+    pub usernames: Vec<String>,
+}
+
+impl FollowProfilesRequest {
+    pub fn validate(&self) -> Result<(), AppError> {
+        let mut errors = ValidationErrors::default();
+        if self.usernames.len() > MAX_BATCH_USERNAMES {
+            errors.add(
+                "usernames",
+                &format!("is too long (maximum is {} usernames)", MAX_BATCH_USERNAMES),
+            );
+        }
+        errors.into_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch(count: usize) -> FollowProfilesRequest {
+        FollowProfilesRequest {
+            usernames: (0..count).map(|i| format!("user-{}", i)).collect(),
+        }
+    }
+
+    #[test]
+    fn batch_accepts_up_to_max_usernames() {
+        assert!(batch(MAX_BATCH_USERNAMES).validate().is_ok());
+    }
+
+    #[test]
+    fn batch_rejects_too_many_usernames() {
+        assert!(batch(MAX_BATCH_USERNAMES + 1).validate().is_err());
+    }
+}
@@ -1,12 +1,16 @@
 use crate::app::profile::model::Profile as ProfileModel;
+use crate::utils::date::Iso8601;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::convert::From;
-#[derive(Deserialize, Serialize, Debug, Clone)]
+use utoipa::ToSchema;
+
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
 pub struct ProfileResponse {
     pub profile: ProfileContent,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
 pub struct ProfileContent {
     pub username: String,
     pub bio: Option<String>,
@@ -25,3 +29,59 @@ impl From<ProfileModel> for ProfileResponse {
         ProfileResponse { profile }
     }
 }
+
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MultipleProfilesResponse {
+    pub profiles: Vec<ProfileContent>,
+    pub profiles_count: i64,
+}
+
+impl From<(Vec<ProfileModel>, i64)> for MultipleProfilesResponse {
+    fn from((list, profiles_count): (Vec<ProfileModel>, i64)) -> Self {
+        let profiles = list
+            .into_iter()
+            .map(|profile_model| ProfileResponse::from(profile_model).profile)
+            .collect();
+        MultipleProfilesResponse {
+            profiles,
+            profiles_count,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FollowProfilesResponse {
+    pub profiles: Vec<ProfileContent>,
+    pub not_found: Vec<String>,
+}
+
+impl From<(Vec<ProfileModel>, Vec<String>)> for FollowProfilesResponse {
+    fn from((list, not_found): (Vec<ProfileModel>, Vec<String>)) -> Self {
+        let profiles = list
+            .into_iter()
+            .map(|profile_model| ProfileResponse::from(profile_model).profile)
+            .collect();
+        FollowProfilesResponse {
+            profiles,
+            not_found,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+pub struct FollowStatusResponse {
+    pub following: bool,
+    #[schema(value_type = Option<String>)]
+    pub since: Option<Iso8601>,
+}
+
+impl From<Option<DateTime<Utc>>> for FollowStatusResponse {
+    fn from(since: Option<DateTime<Utc>>) -> Self {
+        FollowStatusResponse {
+            following: since.is_some(),
+            since: since.map(Iso8601),
+        }
+    }
+}
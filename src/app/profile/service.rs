@@ -1,7 +1,12 @@
 use super::model::Profile;
+use crate::app::follow::model::{CreateFollow, Follow};
 use crate::app::user::model::User;
 use crate::error::AppError;
+use crate::schema::{follows, users};
+use chrono::{DateTime, Utc};
 use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use std::collections::HashSet;
 
 pub struct FetchProfileByName {
     pub current_user: User,
@@ -17,11 +22,22 @@ pub fn fetch_by_name(
 ) -> Result<Profile, AppError> {
     let profile = {
         let followee = User::find_by_username(conn, username)?;
-        current_user.fetch_profile(conn, &followee.id)?
+        current_user.fetch_profile(conn, &followee)?
     };
     Ok(profile)
 }
 
+// Since when `current_user` follows `username`, or `None` when they don't.
+pub fn fetch_follow_since(
+    conn: &PgConnection,
+    current_user: &User,
+    username: &str,
+) -> Result<Option<DateTime<Utc>>, AppError> {
+    let followee = User::find_by_username(conn, username)?;
+    let follow = Follow::find(conn, &current_user.id, &followee.id)?;
+    Ok(follow.map(|follow| follow.created_at))
+}
+
 pub struct ConverUserToProfile<'a> {
     pub user: &'a User,
     pub current_user: &'a Option<User>,
@@ -33,10 +49,118 @@ pub fn conver_user_to_profile(conn: &PgConnection, params: &ConverUserToProfile)
         None => false,
     };
 
-    Profile {
-        username: params.user.username.to_owned(),
-        bio: params.user.bio.to_owned(),
-        image: params.user.image.to_owned(),
-        following,
-    }
+    Profile::from_user(params.user, following)
+}
+
+type ProfilesCount = i64;
+type NotFoundUsernames = Vec<String>;
+
+// Follows every known user in `usernames` at once, so either all of them are followed or none.
+// Profiles come back in the requested order; unknown usernames are reported instead of failing.
+pub fn follow_many(
+    conn: &PgConnection,
+    current_user: &User,
+    usernames: &[String],
+) -> Result<(Vec<Profile>, NotFoundUsernames), AppError> {
+    conn.transaction::<_, AppError, _>(|| {
+        let found = users::table
+            .filter(users::username.eq_any(usernames))
+            .load::<User>(conn)?;
+
+        let mut seen = HashSet::new();
+        let mut profiles = vec![];
+        let mut not_found = vec![];
+        for username in usernames {
+            if !seen.insert(username) {
+                continue;
+            }
+            match found.iter().find(|user| &user.username == username) {
+                // Nobody can follow themselves, so their own profile stays unfollowed.
+                Some(user) if user.id == current_user.id => {
+                    profiles.push(Profile::from_user(user, false))
+                }
+                Some(user) => {
+                    Follow::create(
+                        conn,
+                        &CreateFollow {
+                            follower_id: current_user.id,
+                            followee_id: user.id,
+                        },
+                    )?;
+                    profiles.push(Profile::from_user(user, true));
+                }
+                None => not_found.push(username.to_owned()),
+            }
+        }
+        Ok((profiles, not_found))
+    })
+}
+
+// Users following `username`, most recent follow first.
+pub fn list_followers(
+    conn: &PgConnection,
+    username: &str,
+    current_user: Option<&User>,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<Profile>, ProfilesCount), AppError> {
+    let user = User::find_by_username(conn, username)?;
+    let followers = follows::table
+        .inner_join(users::table.on(users::id.eq(follows::follower_id)))
+        .filter(follows::followee_id.eq(user.id))
+        .order((follows::created_at.desc(), users::username.asc()))
+        .limit(limit)
+        .offset(offset)
+        .select(users::all_columns)
+        .load::<User>(conn)?;
+    let count = follows::table
+        .filter(follows::followee_id.eq(user.id))
+        .count()
+        .get_result::<i64>(conn)?;
+    let profiles = to_profiles_list(conn, followers, current_user)?;
+    Ok((profiles, count))
+}
+
+// Users `username` follows, most recent follow first.
+pub fn list_following(
+    conn: &PgConnection,
+    username: &str,
+    current_user: Option<&User>,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<Profile>, ProfilesCount), AppError> {
+    let user = User::find_by_username(conn, username)?;
+    let followees = follows::table
+        .inner_join(users::table.on(users::id.eq(follows::followee_id)))
+        .filter(follows::follower_id.eq(user.id))
+        .order((follows::created_at.desc(), users::username.asc()))
+        .limit(limit)
+        .offset(offset)
+        .select(users::all_columns)
+        .load::<User>(conn)?;
+    let count = follows::table
+        .filter(follows::follower_id.eq(user.id))
+        .count()
+        .get_result::<i64>(conn)?;
+    let profiles = to_profiles_list(conn, followees, current_user)?;
+    Ok((profiles, count))
+}
+
+fn to_profiles_list(
+    conn: &PgConnection,
+    users_list: Vec<User>,
+    current_user: Option<&User>,
+) -> Result<Vec<Profile>, AppError> {
+    let followee_ids = match current_user {
+        Some(current_user) => {
+            let user_ids = users_list.iter().map(|user| user.id).collect::<Vec<_>>();
+            User::followees_among(conn, &current_user.id, &user_ids)?
+        }
+        None => HashSet::new(),
+    };
+    let profiles = users_list
+        .into_iter()
+        .map(|user| Profile::from_user(&user, followee_ids.contains(&user.id)))
+        .collect();
+    Ok(profiles)
 }
@@ -1,13 +1,48 @@
 extern crate serde_json;
 use super::model::Tag;
-use super::response::TagsResponse;
+use super::response::{PopularTag, TagsResponse};
+use crate::config::PaginationConfig;
 use crate::middleware::state::AppState;
 use crate::utils::api::ApiResponse;
-use actix_web::{web, HttpResponse};
+use crate::utils::pagination::{self, Pagination};
+use actix_web::{web, HttpRequest, HttpResponse};
 
+// Fewer than `PAGINATION_DEFAULT_LIMIT`, a tag cloud rarely shows more.
+const POPULAR_DEFAULT_LIMIT: i64 = 10;
+
+#[utoipa::path(
+    get,
+    path = "/api/tags",
+    tag = "tag",
+    responses(
+        (status = 200, description = "List of tags", body = TagsResponse),
+    ),
+)]
 pub async fn index(state: web::Data<AppState>) -> ApiResponse {
-    let conn = state.get_conn()?;
-    let list = Tag::fetch(&conn)?;
+    let conn = state.read_conn()?;
+    let list = Tag::list_all(&conn)?;
     let res = TagsResponse::from(list);
     Ok(HttpResponse::Ok().json(res))
 }
+
+#[utoipa::path(
+    get,
+    path = "/api/tags/popular",
+    tag = "tag",
+    params(pagination::PaginationQuery),
+    responses(
+        (status = 200, description = "Tags ordered by usage", body = [PopularTag]),
+        (status = 400, description = "Invalid pagination", body = crate::middleware::error::ErrorResponse),
+    ),
+)]
+pub async fn popular(state: web::Data<AppState>, req: HttpRequest) -> ApiResponse {
+    let config = PaginationConfig {
+        default_limit: std::cmp::min(POPULAR_DEFAULT_LIMIT, state.config.pagination.max_limit),
+        max_limit: state.config.pagination.max_limit,
+    };
+    let Pagination { limit, offset } = Pagination::from_query(&config, req.query_string())?;
+    let conn = state.read_conn()?;
+    let list = Tag::popular(&conn, limit, offset)?;
+    let res = list.into_iter().map(PopularTag::from).collect::<Vec<_>>();
+    Ok(HttpResponse::Ok().json(res))
+}
@@ -1,11 +1,12 @@
 use crate::app::article::model::Article;
 use crate::error::AppError;
 use crate::schema::tags;
-use chrono::NaiveDateTime;
+use chrono::{DateTime, Utc};
 use diesel::pg::PgConnection;
 use diesel::Insertable;
 use diesel::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(Identifiable, Queryable, Debug, Serialize, Deserialize, Clone, Associations)]
@@ -15,11 +16,16 @@ pub struct Tag {
     pub id: Uuid,
     pub article_id: Uuid,
     pub name: String,
-    pub created_at: NaiveDateTime,
-    pub updated_at: NaiveDateTime,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
 impl Tag {
+    // "Rust", " rust" and "RUST" are the same tag, stored and looked up as "rust".
+    pub fn normalize_name(name: &str) -> String {
+        name.trim().to_lowercase()
+    }
+
     pub fn fetch_by_article_id(
         conn: &PgConnection,
         article_id: Uuid,
@@ -30,14 +36,58 @@ impl Tag {
         Ok(list)
     }
 
-    pub fn fetch(conn: &PgConnection) -> Result<Vec<Self>, AppError> {
-        let list = tags::table.load::<Self>(conn)?;
+    // Each article's tags are ordered by name; untagged articles are missing from the map.
+    pub fn fetch_by_article_ids(
+        conn: &PgConnection,
+        article_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<Self>>, AppError> {
+        let list = tags::table
+            .filter(tags::article_id.eq_any(article_ids))
+            .order(tags::name.asc())
+            .load::<Self>(conn)?;
+        let mut map = HashMap::<Uuid, Vec<Self>>::new();
+        for tag in list {
+            map.entry(tag.article_id).or_default().push(tag);
+        }
+        Ok(map)
+    }
+
+    // Each name once, ordered by name. The id breaks ties, so the row kept for a name (and
+    // thus the whole list) is the same on every call. Tags only on soft-deleted articles are left out.
+    pub fn list_all(conn: &PgConnection) -> Result<Vec<Self>, AppError> {
+        use crate::schema::articles;
+        let list = tags::table
+            .inner_join(articles::table)
+            .filter(articles::deleted_at.is_null())
+            .select(tags::all_columns)
+            .distinct_on(tags::name)
+            .order((tags::name.asc(), tags::id.asc()))
+            .load::<Self>(conn)?;
+        Ok(list)
+    }
+
+    // Returns (name, articles count) ordered by usage, then by name for ties.
+    pub fn popular(
+        conn: &PgConnection,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<(String, i64)>, AppError> {
+        use crate::schema::articles;
+        let list = tags::table
+            .inner_join(articles::table)
+            .filter(articles::deleted_at.is_null())
+            .group_by(tags::name)
+            .select((tags::name, diesel::dsl::count(tags::id)))
+            .order((diesel::dsl::count(tags::id).desc(), tags::name.asc()))
+            .limit(limit)
+            .offset(offset)
+            .load::<(String, i64)>(conn)?;
         Ok(list)
     }
 
     pub fn fetch_ids_by_name(conn: &PgConnection, tag_name: &str) -> Result<Vec<Uuid>, AppError> {
         let ids = tags::table
-            .filter(tags::name.eq(tag_name))
+            .filter(tags::name.eq(Self::normalize_name(tag_name)))
             .select(tags::article_id)
             .load::<Uuid>(conn)?;
         Ok(ids)
@@ -52,6 +102,11 @@ impl Tag {
             .get_results::<Tag>(conn)?;
         Ok(tags_list)
     }
+
+    pub fn delete_list(conn: &PgConnection, ids: Vec<Uuid>) -> Result<(), AppError> {
+        let _ = diesel::delete(tags::table.filter(tags::id.eq_any(ids))).execute(conn)?;
+        Ok(())
+    }
 }
 
 #[derive(Insertable)]
@@ -60,3 +115,97 @@ pub struct CreateTag<'a> {
     pub name: &'a str,
     pub article_id: &'a Uuid,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::db::establish_test_connection;
+    use crate::utils::test_support::{create_article, signup_user};
+
+    #[test]
+    fn normalize_name_trims_and_lowercases() {
+        assert_eq!(Tag::normalize_name("  Rust "), "rust");
+        assert_eq!(Tag::normalize_name("RUST"), "rust");
+        assert_eq!(Tag::normalize_name("rust"), "rust");
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn popular_orders_by_usage_then_name() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "popular-tags");
+        create_article(&conn, user.id, "popular-1", &["zz-popular", "bb-popular"]);
+        create_article(&conn, user.id, "popular-2", &["zz-popular", "aa-popular"]);
+        create_article(&conn, user.id, "popular-3", &["zz-popular"]);
+
+        let list = Tag::popular(&conn, 100, 0).unwrap();
+        let list = list
+            .into_iter()
+            .filter(|(name, _)| name.ends_with("-popular"))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            list,
+            vec![
+                ("zz-popular".to_owned(), 3),
+                ("aa-popular".to_owned(), 1),
+                ("bb-popular".to_owned(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn list_all_is_distinct_and_stable() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "list-all-tags");
+        create_article(
+            &conn,
+            user.id,
+            "list-all-1",
+            &["zz-list-all", "aa-list-all"],
+        );
+        create_article(
+            &conn,
+            user.id,
+            "list-all-2",
+            &["mm-list-all", "zz-list-all"],
+        );
+        create_article(&conn, user.id, "list-all-3", &["aa-list-all"]);
+
+        let first = Tag::list_all(&conn).unwrap();
+        let second = Tag::list_all(&conn).unwrap();
+
+        let ids = |list: &[Tag]| list.iter().map(|tag| tag.id).collect::<Vec<_>>();
+        assert_eq!(ids(&first), ids(&second));
+        let names = first
+            .into_iter()
+            .map(|tag| tag.name)
+            .filter(|name| name.ends_with("-list-all"))
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["aa-list-all", "mm-list-all", "zz-list-all"]);
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn list_all_skips_soft_deleted_articles() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "list-all-deleted");
+        create_article(&conn, user.id, "list-all-kept", &["kept-list-all"]);
+        let deleted = create_article(
+            &conn,
+            user.id,
+            "list-all-deleted",
+            &["kept-list-all", "gone-list-all"],
+        );
+        Article::soft_delete(&conn, &deleted.id).unwrap();
+
+        let names = Tag::list_all(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|tag| tag.name)
+            .filter(|name| name.ends_with("-list-all"))
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["kept-list-all"]);
+    }
+}
@@ -1,7 +1,8 @@
 use super::model::Tag;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
 pub struct TagsResponse {
     // SPEC: https://gothinkster.github.io/realworld/docs/specs/backend-specs/endpoints#registration
     pub tags: Vec<String>,
@@ -13,3 +14,15 @@ impl std::convert::From<Vec<Tag>> for TagsResponse {
         TagsResponse { tags: list }
     }
 }
+
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+pub struct PopularTag {
+    pub name: String,
+    pub count: i64,
+}
+
+impl std::convert::From<(String, i64)> for PopularTag {
+    fn from((name, count): (String, i64)) -> Self {
+        PopularTag { name, count }
+    }
+}
@@ -1,42 +1,138 @@
 use super::model::{UpdateUser, User};
 use super::{request, response::UserResponse};
+use crate::error::AppError;
 use crate::middleware::auth;
+use crate::middleware::error::ErrorResponse;
 use crate::middleware::state::AppState;
 use crate::utils::api::ApiResponse;
+use crate::utils::validator::ValidationErrors;
+use crate::utils::{hasher, storage};
+use actix_multipart::{Multipart, MultipartError};
 use actix_web::{web, HttpRequest, HttpResponse};
+use futures::TryStreamExt;
+use serde_json::json;
+use uuid::Uuid;
 
+#[utoipa::path(
+    post,
+    path = "/api/users/login",
+    tag = "user",
+    request_body = request::Signin,
+    responses(
+        (status = 200, description = "Authenticated user", body = UserResponse),
+        (status = 401, description = "Invalid credentials", body = crate::middleware::error::ErrorResponse),
+        (status = 422, description = "Neither or both of email and username", body = crate::middleware::error::ErrorResponse),
+    ),
+)]
 pub async fn signin(state: web::Data<AppState>, form: web::Json<request::Signin>) -> ApiResponse {
-    let conn = state.get_conn()?;
-    let (user, token) = User::signin(&conn, &form.user.email, &form.user.password)?;
+    let signin_by = form.validate()?;
+    let conn = state.write_conn()?;
+    let record_login = !state.is_read_only();
+    let (user, token) = match signin_by {
+        request::SigninBy::Email(email) => User::signin(
+            &conn,
+            &email,
+            &form.user.password,
+            &state.config.token,
+            record_login,
+        )?,
+        request::SigninBy::Username(username) => User::signin_by_username(
+            &conn,
+            &username,
+            &form.user.password,
+            &state.config.token,
+            record_login,
+        )?,
+    };
     let res = UserResponse::from((user, token));
     Ok(HttpResponse::Ok().json(res))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/users",
+    tag = "user",
+    request_body = request::Signup,
+    responses(
+        (status = 200, description = "Registered user", body = UserResponse),
+        (status = 403, description = "Registration is disabled", body = crate::middleware::error::ErrorResponse),
+        (status = 422, description = "Invalid user", body = crate::middleware::error::ErrorResponse),
+    ),
+)]
 pub async fn signup(state: web::Data<AppState>, form: web::Json<request::Signup>) -> ApiResponse {
-    let conn = state.get_conn()?;
+    if !state.config.signup_enabled {
+        return Err(AppError::Forbidden(json!(ErrorResponse::from(
+            "registration is disabled"
+        ))));
+    }
+    form.validate(&state.config)?;
+    let conn = state.write_conn()?;
     let (user, token) = User::signup(
         &conn,
         &form.user.email,
         &form.user.username,
         &form.user.password,
+        state.config.bcrypt_cost,
+        &state.config.token,
     )?;
     let res = UserResponse::from((user, token));
     Ok(HttpResponse::Ok().json(res))
 }
 
-pub async fn me(req: HttpRequest) -> ApiResponse {
+#[utoipa::path(
+    get,
+    path = "/api/user",
+    tag = "user",
+    responses(
+        (status = 200, description = "Current user", body = UserResponse),
+        (status = 401, description = "Unauthorized", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
+pub async fn me(state: web::Data<AppState>, req: HttpRequest) -> ApiResponse {
     let user = auth::get_current_user(&req)?;
-    let token = user.generate_token()?;
+    let token = user.generate_token(&state.config.token)?;
     let res = UserResponse::from((user, token));
     Ok(HttpResponse::Ok().json(res))
 }
 
+// The auth middleware has already rejected expired tokens, so only a live session is extended.
+#[utoipa::path(
+    post,
+    path = "/api/user/refresh",
+    tag = "user",
+    responses(
+        (status = 200, description = "Current user with a token valid for another day", body = UserResponse),
+        (status = 401, description = "Missing, invalid or expired token", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
+pub async fn refresh(state: web::Data<AppState>, req: HttpRequest) -> ApiResponse {
+    let user = auth::get_current_user(&req)?;
+    let token = user.generate_token(&state.config.token)?;
+    let res = UserResponse::from((user, token));
+    Ok(HttpResponse::Ok().json(res))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/user",
+    tag = "user",
+    request_body = request::Update,
+    responses(
+        (status = 200, description = "Updated user", body = UserResponse),
+        (status = 401, description = "Unauthorized", body = crate::middleware::error::ErrorResponse),
+        (status = 422, description = "Invalid user, or email or username already taken", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
 pub async fn update(
     state: web::Data<AppState>,
     req: HttpRequest,
     form: web::Json<request::Update>,
 ) -> ApiResponse {
-    let conn = state.get_conn()?;
+    form.validate(&state.config)?;
+    let conn = state.write_conn()?;
     let current_user = auth::get_current_user(&req)?;
     let user = User::update(
         &conn,
@@ -44,12 +140,131 @@ pub async fn update(
         UpdateUser {
             email: form.user.email.clone(),
             username: form.user.username.clone(),
-            password: form.user.password.clone(),
+            password: form
+                .user
+                .password
+                .as_deref()
+                .map(|password| hasher::hash_password(password, state.config.bcrypt_cost))
+                .transpose()?,
             image: form.user.image.clone(),
             bio: form.user.bio.clone(),
         },
     )?;
-    let token = &user.generate_token()?;
+    let token = &user.generate_token(&state.config.token)?;
     let res = UserResponse::from((user, token.to_string()));
     Ok(HttpResponse::Ok().json(res))
 }
+
+#[utoipa::path(
+    delete,
+    path = "/api/user",
+    tag = "user",
+    responses(
+        (status = 204, description = "Account deleted"),
+        (status = 401, description = "Unauthorized", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
+pub async fn delete(state: web::Data<AppState>, req: HttpRequest) -> ApiResponse {
+    let conn = state.write_conn()?;
+    let current_user = auth::get_current_user(&req)?;
+    User::delete(&conn, current_user.id)?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/user/image",
+    tag = "user",
+    request_body(
+        content = String,
+        description = "Multipart form with a png, jpeg, gif or webp file in the `image` field",
+        content_type = "multipart/form-data",
+    ),
+    responses(
+        (status = 200, description = "User with the uploaded image", body = UserResponse),
+        (status = 401, description = "Unauthorized", body = crate::middleware::error::ErrorResponse),
+        (status = 413, description = "Image too large", body = crate::middleware::error::ErrorResponse),
+        (status = 415, description = "Not an accepted image type", body = crate::middleware::error::ErrorResponse),
+        (status = 422, description = "No image in the form", body = crate::middleware::error::ErrorResponse),
+        (status = 501, description = "Image uploads are not configured", body = crate::middleware::error::ErrorResponse),
+    ),
+    security(("token" = [])),
+)]
+pub async fn upload_image(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    mut payload: Multipart,
+) -> ApiResponse {
+    let s3 = state.config.s3.as_ref().ok_or_else(|| {
+        AppError::NotImplemented(json!(ErrorResponse::from(
+            "image uploads are not configured"
+        )))
+    })?;
+    let current_user = auth::get_current_user(&req)?;
+    let (bytes, content_type, extension) =
+        read_image(&mut payload, state.config.image_upload_limit).await?;
+    let key = format!("users/{}/{}.{}", current_user.id, Uuid::new_v4(), extension);
+    let url = storage::upload(s3, &key, &bytes, &content_type).await?;
+
+    let conn = state.write_conn()?;
+    let user = User::update(
+        &conn,
+        current_user.id,
+        UpdateUser {
+            email: None,
+            username: None,
+            password: None,
+            image: Some(Some(url)),
+            bio: None,
+        },
+    )?;
+    let token = user.generate_token(&state.config.token)?;
+    let res = UserResponse::from((user, token));
+    Ok(HttpResponse::Ok().json(res))
+}
+
+type ImageBytes = Vec<u8>;
+type ContentType = String;
+type Extension = &'static str;
+
+// Reads the `image` field of the form, rejecting it as soon as it grows past `limit` bytes.
+async fn read_image(
+    payload: &mut Multipart,
+    limit: usize,
+) -> Result<(ImageBytes, ContentType, Extension), AppError> {
+    while let Some(mut field) = payload.try_next().await.map_err(invalid_multipart)? {
+        if field.name() != "image" {
+            continue;
+        }
+        let content_type = field.content_type().essence_str().to_owned();
+        let extension = storage::image_extension(&content_type).ok_or_else(|| {
+            AppError::UnsupportedMediaType(json!(ErrorResponse::from(
+                "image must be png, jpeg, gif or webp"
+            )))
+        })?;
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.try_next().await.map_err(invalid_multipart)? {
+            if bytes.len() + chunk.len() > limit {
+                return Err(AppError::PayloadTooLarge(json!(ErrorResponse::from(
+                    "image too large"
+                ))));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        if !storage::has_image_signature(&content_type, &bytes) {
+            return Err(AppError::UnsupportedMediaType(json!(ErrorResponse::from(
+                "image content doesn't match its content type"
+            ))));
+        }
+        return Ok((bytes, content_type, extension));
+    }
+    let mut errors = ValidationErrors::default();
+    errors.add("image", "can't be blank");
+    Err(errors.into_result().expect_err("an error was added"))
+}
+
+fn invalid_multipart(err: MultipartError) -> AppError {
+    info!("Invalid multipart body: {}", err);
+    AppError::BadRequest(json!(ErrorResponse::from("invalid multipart body")))
+}
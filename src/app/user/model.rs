@@ -1,13 +1,18 @@
 use crate::app::follow::model::{CreateFollow, DeleteFollow, Follow};
 use crate::app::profile::model::Profile;
+use crate::config::TokenConfig;
 use crate::error::AppError;
+use crate::middleware::error::ErrorResponse;
 use crate::schema::users;
+use crate::utils::validator::ValidationErrors;
 use crate::utils::{hasher, token};
-use chrono::prelude::*;
-use chrono::NaiveDateTime;
+use chrono::{DateTime, Utc};
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 #[derive(Identifiable, Queryable, Serialize, Deserialize, Debug, Clone, Associations)]
@@ -19,21 +24,50 @@ pub struct User {
     pub password: String,
     pub bio: Option<String>,
     pub image: Option<String>,
-    pub created_at: NaiveDateTime,
-    pub updated_at: NaiveDateTime,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    // For account analytics only, never part of a response.
+    #[serde(skip_serializing)]
+    pub last_login_at: Option<DateTime<Utc>>,
 }
 
 type Token = String;
 
+const TAKEN_MESSAGE: &str = "has already been taken";
+
+fn taken_error(err: DieselError) -> AppError {
+    let field = match &err {
+        DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, info) => {
+            match info.constraint_name() {
+                Some("users_email_key") => Some("email"),
+                Some("users_username_key") => Some("username"),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    match field {
+        Some(field) => {
+            let mut errors = ValidationErrors::default();
+            errors.add(field, TAKEN_MESSAGE);
+            errors.into_result().expect_err("an error was added")
+        }
+        None => err.into(),
+    }
+}
+
 impl User {
     pub fn signup<'a>(
         conn: &PgConnection,
         email: &'a str,
         username: &'a str,
         naive_password: &'a str,
+        bcrypt_cost: u32,
+        token_config: &TokenConfig,
     ) -> Result<(User, Token), AppError> {
         use diesel::prelude::*;
-        let hashed_password = hasher::hash_password(naive_password)?;
+        // Hashing is slow, so it happens before the transaction is opened.
+        let hashed_password = hasher::hash_password(naive_password, bcrypt_cost)?;
 
         let record = SignupUser {
             email,
@@ -41,25 +75,78 @@ impl User {
             password: &hashed_password,
         };
 
-        let user = diesel::insert_into(users::table)
-            .values(&record)
-            .get_result::<User>(conn)?;
+        conn.transaction::<_, AppError, _>(|| {
+            Self::check_uniqueness(conn, email, username)?;
+            // NOTE: a concurrent signup can still pass the check above, in which case the unique
+            // constraints reject this insert.
+            let user = diesel::insert_into(users::table)
+                .values(&record)
+                .get_result::<User>(conn)
+                .map_err(taken_error)?;
+            let token = user.generate_token(token_config)?;
+            Ok((user, token))
+        })
+    }
 
-        let token = user.generate_token()?;
-        Ok((user, token))
+    fn check_uniqueness(conn: &PgConnection, email: &str, username: &str) -> Result<(), AppError> {
+        use diesel::dsl::{exists, select};
+        let mut errors = ValidationErrors::default();
+        if select(exists(users::table.filter(users::email.eq(email)))).get_result(conn)? {
+            errors.add("email", TAKEN_MESSAGE);
+        }
+        if select(exists(users::table.filter(users::username.eq(username)))).get_result(conn)? {
+            errors.add("username", TAKEN_MESSAGE);
+        }
+        errors.into_result()
     }
 
     pub fn signin(
         conn: &PgConnection,
         email: &str,
         naive_password: &str,
+        token_config: &TokenConfig,
+        record_login: bool,
     ) -> Result<(User, Token), AppError> {
         let user = users::table
             .filter(users::email.eq(email))
             .limit(1)
             .first::<User>(conn)?;
-        let _ = hasher::verify(&naive_password, &user.password)?;
-        let token = user.generate_token()?;
+        user.authenticate(conn, naive_password, token_config, record_login)
+    }
+
+    pub fn signin_by_username(
+        conn: &PgConnection,
+        username: &str,
+        naive_password: &str,
+        token_config: &TokenConfig,
+        record_login: bool,
+    ) -> Result<(User, Token), AppError> {
+        let user = Self::find_by_username(conn, username)?;
+        user.authenticate(conn, naive_password, token_config, record_login)
+    }
+
+    // `record_login` is off in read-only mode, where signing in must not write.
+    fn authenticate(
+        self,
+        conn: &PgConnection,
+        naive_password: &str,
+        token_config: &TokenConfig,
+        record_login: bool,
+    ) -> Result<(User, Token), AppError> {
+        if !hasher::verify(naive_password, &self.password)? {
+            return Err(AppError::Unauthorized(json!(ErrorResponse::from(
+                "PW is invalid"
+            ))));
+        }
+        let user = if record_login {
+            // NOTE: the clock of the app rather than `now()`, which is frozen within a transaction.
+            diesel::update(users::table.find(self.id))
+                .set(users::last_login_at.eq(Utc::now()))
+                .get_result::<User>(conn)?
+        } else {
+            self
+        };
+        let token = user.generate_token(token_config)?;
         Ok((user, token))
     }
 
@@ -68,6 +155,14 @@ impl User {
         Ok(user)
     }
 
+    // A single query for lists; unknown ids are absent from the map.
+    pub fn find_by_ids(conn: &PgConnection, ids: &[Uuid]) -> Result<HashMap<Uuid, Self>, AppError> {
+        let users = users::table
+            .filter(users::id.eq_any(ids))
+            .load::<Self>(conn)?;
+        Ok(users.into_iter().map(|user| (user.id, user)).collect())
+    }
+
     pub fn update(
         conn: &PgConnection,
         user_id: Uuid,
@@ -80,6 +175,26 @@ impl User {
         Ok(user)
     }
 
+    // Comments, favorites and tags go away with their articles (and the user) via ON DELETE
+    // CASCADE, but follows don't cascade, so both directions are removed explicitly.
+    pub fn delete(conn: &PgConnection, user_id: Uuid) -> Result<(), AppError> {
+        use crate::schema::{articles, follows};
+        conn.transaction::<_, AppError, _>(|| {
+            diesel::delete(articles::table.filter(articles::author_id.eq(user_id)))
+                .execute(conn)?;
+            diesel::delete(
+                follows::table.filter(
+                    follows::follower_id
+                        .eq(user_id)
+                        .or(follows::followee_id.eq(user_id)),
+                ),
+            )
+            .execute(conn)?;
+            diesel::delete(users::table.find(user_id)).execute(conn)?;
+            Ok(())
+        })
+    }
+
     pub fn find_by_username(conn: &PgConnection, username: &str) -> Result<Self, AppError> {
         let user = users::table
             .filter(users::username.eq(username))
@@ -101,12 +216,7 @@ impl User {
             },
         )?;
 
-        Ok(Profile {
-            username: self.username.clone(),
-            bio: self.bio.clone(),
-            image: self.image.clone(),
-            following: true,
-        })
+        Ok(Profile::from_user(&followee, true))
     }
 
     pub fn unfollow(&self, conn: &PgConnection, username: &str) -> Result<Profile, AppError> {
@@ -122,12 +232,7 @@ impl User {
             },
         )?;
 
-        Ok(Profile {
-            username: self.username.clone(),
-            bio: self.bio.clone(),
-            image: self.image.clone(),
-            following: false,
-        })
+        Ok(Profile::from_user(&followee, false))
     }
 
     pub fn is_following(&self, conn: &PgConnection, followee_id: &Uuid) -> bool {
@@ -138,12 +243,26 @@ impl User {
             .get_result::<Follow>(conn);
         follow.is_ok()
     }
+
+    // The batch counterpart of `is_following`, for rendering lists of profiles in one query.
+    pub fn followees_among(
+        conn: &PgConnection,
+        follower_id: &Uuid,
+        candidate_ids: &[Uuid],
+    ) -> Result<HashSet<Uuid>, AppError> {
+        use crate::schema::follows;
+        let ids = follows::table
+            .filter(follows::follower_id.eq(follower_id))
+            .filter(follows::followee_id.eq_any(candidate_ids))
+            .select(follows::followee_id)
+            .get_results::<Uuid>(conn)?;
+        Ok(ids.into_iter().collect())
+    }
 }
 
 impl User {
-    pub fn generate_token(&self) -> Result<String, AppError> {
-        let now = Utc::now().timestamp_nanos() / 1_000_000_000; // nanosecond -> second
-        let token = token::generate(self.id, now)?;
+    pub fn generate_token(&self, config: &TokenConfig) -> Result<String, AppError> {
+        let token = token::generate(self.id, Utc::now(), config)?;
         Ok(token)
     }
 
@@ -156,19 +275,9 @@ impl User {
         Ok(favorited_article_ids)
     }
 
-    pub fn fetch_profile(
-        &self,
-        conn: &PgConnection,
-        folowee_id: &Uuid,
-    ) -> Result<Profile, AppError> {
-        let is_following = &self.is_following(conn, folowee_id);
-        let profile = Profile {
-            username: self.username.to_owned(),
-            bio: self.bio.to_owned(),
-            image: self.image.to_owned(),
-            following: is_following.to_owned(),
-        };
-        Ok(profile)
+    pub fn fetch_profile(&self, conn: &PgConnection, followee: &User) -> Result<Profile, AppError> {
+        let following = self.is_following(conn, &followee.id);
+        Ok(Profile::from_user(followee, following))
     }
 }
 
@@ -186,6 +295,225 @@ pub struct UpdateUser {
     pub email: Option<String>,
     pub username: Option<String>,
     pub password: Option<String>,
-    pub image: Option<String>,
-    pub bio: Option<String>,
+    pub image: Option<Option<String>>,
+    pub bio: Option<Option<String>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::comment::model::{Comment, CreateComment};
+    use crate::app::favorite::model::{CreateFavorite, Favorite};
+    use crate::schema::{articles, comments, favorites, follows, tags};
+    use crate::utils::db::establish_test_connection;
+    use crate::utils::test_support::{create_article, signup_user};
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn timestamps_round_trip_regardless_of_session_time_zone() {
+        use chrono::TimeZone;
+        let conn = establish_test_connection();
+        conn.execute("SET TIME ZONE 'Asia/Tokyo'").unwrap();
+        let user = signup_user(&conn, "round-trip");
+        let at = Utc.ymd(2021, 10, 24).and_hms_micro(23, 7, 44, 123_456);
+
+        diesel::update(users::table.find(user.id))
+            .set(users::last_login_at.eq(at))
+            .execute(&conn)
+            .unwrap();
+
+        assert_eq!(User::find(&conn, user.id).unwrap().last_login_at, Some(at));
+        let rendered = users::table
+            .find(user.id)
+            .select(diesel::dsl::sql::<diesel::sql_types::Text>(
+                "to_char(last_login_at AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS.US')",
+            ))
+            .get_result::<String>(&conn)
+            .unwrap();
+        assert_eq!(rendered, "2021-10-24 23:07:44.123456");
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn signin_advances_last_login_at() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "last-login");
+        assert_eq!(user.last_login_at, None);
+
+        let (first, _) = User::signin(
+            &conn,
+            "last-login@example.com",
+            "password",
+            &TokenConfig::default(),
+            true,
+        )
+        .unwrap();
+        let (second, _) = User::signin_by_username(
+            &conn,
+            "last-login",
+            "password",
+            &TokenConfig::default(),
+            true,
+        )
+        .unwrap();
+
+        let first = first
+            .last_login_at
+            .expect("first signin sets last_login_at");
+        let second = second
+            .last_login_at
+            .expect("second signin sets last_login_at");
+        assert!(first < second);
+        assert_eq!(
+            User::find(&conn, user.id).unwrap().last_login_at,
+            Some(second)
+        );
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn signin_without_record_login_leaves_last_login_at() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "no-record");
+
+        let (signed_in, _) = User::signin(
+            &conn,
+            "no-record@example.com",
+            "password",
+            &TokenConfig::default(),
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(signed_in.last_login_at, None);
+        assert_eq!(User::find(&conn, user.id).unwrap().last_login_at, None);
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn delete_removes_related_rows() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "delete-me");
+        let other = signup_user(&conn, "delete-other");
+        user.follow(&conn, &other.username).unwrap();
+        other.follow(&conn, &user.username).unwrap();
+        let article = create_article(&conn, user.id, "delete-me-article", &["delete-me-tag"]);
+        Comment::create(
+            &conn,
+            &CreateComment {
+                body: "comment".to_owned(),
+                author_id: other.id,
+                article_id: article.id,
+            },
+        )
+        .unwrap();
+        Favorite::create(
+            &conn,
+            &CreateFavorite {
+                user_id: other.id,
+                article_id: article.id,
+            },
+        )
+        .unwrap();
+
+        User::delete(&conn, user.id).unwrap();
+
+        assert!(User::find(&conn, user.id).is_err());
+        assert!(User::find(&conn, other.id).is_ok());
+        let follows_count = follows::table
+            .filter(
+                follows::follower_id
+                    .eq(user.id)
+                    .or(follows::followee_id.eq(user.id)),
+            )
+            .count()
+            .get_result::<i64>(&conn)
+            .unwrap();
+        assert_eq!(follows_count, 0);
+        let articles_count = articles::table
+            .filter(articles::author_id.eq(user.id))
+            .count()
+            .get_result::<i64>(&conn)
+            .unwrap();
+        assert_eq!(articles_count, 0);
+        let tags_count = tags::table
+            .filter(tags::article_id.eq(article.id))
+            .count()
+            .get_result::<i64>(&conn)
+            .unwrap();
+        assert_eq!(tags_count, 0);
+        let comments_count = comments::table
+            .filter(comments::article_id.eq(article.id))
+            .count()
+            .get_result::<i64>(&conn)
+            .unwrap();
+        assert_eq!(comments_count, 0);
+        let favorites_count = favorites::table
+            .filter(favorites::article_id.eq(article.id))
+            .count()
+            .get_result::<i64>(&conn)
+            .unwrap();
+        assert_eq!(favorites_count, 0);
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn followees_among_returns_followed_candidates() {
+        let conn = establish_test_connection();
+        let follower = signup_user(&conn, "followees-among-follower");
+        let candidates = (0..5)
+            .map(|i| signup_user(&conn, &format!("followees-among-candidate-{}", i)))
+            .collect::<Vec<_>>();
+        follower.follow(&conn, &candidates[1].username).unwrap();
+        follower.follow(&conn, &candidates[3].username).unwrap();
+        let candidate_ids = candidates.iter().map(|user| user.id).collect::<Vec<_>>();
+
+        let followees = User::followees_among(&conn, &follower.id, &candidate_ids).unwrap();
+
+        assert_eq!(
+            followees,
+            HashSet::from([candidates[1].id, candidates[3].id])
+        );
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn signin_with_wrong_password_is_unauthorized() {
+        let conn = establish_test_connection();
+        let user = signup_user(&conn, "wrong-password");
+
+        let result = User::signin(
+            &conn,
+            &user.email,
+            "not-the-password",
+            &TokenConfig::default(),
+            true,
+        );
+
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn signin_with_corrupt_hash_is_internal_server_error() {
+        let conn = establish_test_connection();
+        diesel::insert_into(users::table)
+            .values(&SignupUser {
+                email: "corrupt-hash@example.com",
+                username: "corrupt-hash",
+                password: "not-a-bcrypt-hash",
+            })
+            .execute(&conn)
+            .unwrap();
+
+        let result = User::signin(
+            &conn,
+            "corrupt-hash@example.com",
+            "password",
+            &TokenConfig::default(),
+            true,
+        );
+
+        assert!(matches!(result, Err(AppError::InternalServerError)));
+    }
 }
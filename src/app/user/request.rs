@@ -1,41 +1,263 @@
+use crate::config::Config;
+use crate::error::AppError;
+use crate::utils::validator::{self, ValidationErrors};
+use crate::utils::{nullable, trimmed};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
 pub struct Signup {
     // SPEC: https://gothinkster.github.io/realworld/docs/specs/backend-specs/endpoints#registration
     pub user: SignupUser,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
 pub struct SignupUser {
+    #[serde(deserialize_with = "trimmed::deserialize")]
     pub username: String,
+    #[serde(deserialize_with = "trimmed::deserialize")]
     pub email: String,
     pub password: String,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+impl Signup {
+    pub fn validate(&self, config: &Config) -> Result<(), AppError> {
+        let mut errors = ValidationErrors::default();
+        if !validator::is_email(&self.user.email) {
+            errors.add("email", "is invalid");
+        }
+        validate_password(&mut errors, &self.user.password, config);
+        errors.into_result()
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
 pub struct Signin {
     // SPEC: https://gothinkster.github.io/realworld/docs/specs/backend-specs/endpoints#authentication
     pub user: SigninUser,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
 pub struct SigninUser {
-    pub email: String,
+    // Exactly one of `email` and `username` identifies the user.
+    #[serde(default, deserialize_with = "trimmed::deserialize_option")]
+    pub email: Option<String>,
+    #[serde(default, deserialize_with = "trimmed::deserialize_option")]
+    pub username: Option<String>,
     pub password: String,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+// The identifier a valid `Signin` has, see `Signin::validate`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SigninBy {
+    Email(String),
+    Username(String),
+}
+
+impl Signin {
+    pub fn validate(&self) -> Result<SigninBy, AppError> {
+        let message = match (&self.user.email, &self.user.username) {
+            (Some(email), None) => return Ok(SigninBy::Email(email.clone())),
+            (None, Some(username)) => return Ok(SigninBy::Username(username.clone())),
+            (Some(_), Some(_)) => "must not have both email and username",
+            (None, None) => "must have either email or username",
+        };
+        let mut errors = ValidationErrors::default();
+        errors.add("user", message);
+        Err(errors.into_result().expect_err("an error was added"))
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
 pub struct Update {
     // SPEC: https://gothinkster.github.io/realworld/docs/specs/backend-specs/endpoints#authentication
     pub user: UpdateUser,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
 pub struct UpdateUser {
+    #[serde(default, deserialize_with = "trimmed::deserialize_option")]
     pub email: Option<String>,
+    #[serde(default, deserialize_with = "trimmed::deserialize_option")]
     pub username: Option<String>,
     pub password: Option<String>,
-    pub image: Option<String>,
-    pub bio: Option<String>,
+    // `null` clears the column, while an omitted field leaves it untouched.
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    #[schema(value_type = Option<String>)]
+    pub image: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    #[schema(value_type = Option<String>)]
+    pub bio: Option<Option<String>>,
+}
+
+impl Update {
+    pub fn validate(&self, config: &Config) -> Result<(), AppError> {
+        let mut errors = ValidationErrors::default();
+        // Omitted fields are left as is, but a present one must not blank the column.
+        for (field, value) in [
+            ("email", &self.user.email),
+            ("username", &self.user.username),
+        ] {
+            if value
+                .as_deref()
+                .map_or(false, |value| value.trim().is_empty())
+            {
+                errors.add(field, "can't be blank");
+            }
+        }
+        if let Some(password) = &self.user.password {
+            validate_password(&mut errors, password, config);
+        }
+        errors.into_result()
+    }
+}
+
+fn validate_password(errors: &mut ValidationErrors, password: &str, config: &Config) {
+    if password.chars().count() < config.password_min_length {
+        errors.add(
+            "password",
+            &format!(
+                "is too short (minimum is {} characters)",
+                config.password_min_length
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn signup(email: &str, password: &str) -> Signup {
+        Signup {
+            user: SignupUser {
+                username: "jake".to_owned(),
+                email: email.to_owned(),
+                password: password.to_owned(),
+            },
+        }
+    }
+
+    fn signin(email: Option<&str>, username: Option<&str>) -> Signin {
+        Signin {
+            user: SigninUser {
+                email: email.map(str::to_owned),
+                username: username.map(str::to_owned),
+                password: "jakejake".to_owned(),
+            },
+        }
+    }
+
+    fn update(password: Option<&str>) -> Update {
+        update_identity(None, None, password)
+    }
+
+    fn update_identity(
+        email: Option<&str>,
+        username: Option<&str>,
+        password: Option<&str>,
+    ) -> Update {
+        Update {
+            user: UpdateUser {
+                email: email.map(str::to_owned),
+                username: username.map(str::to_owned),
+                password: password.map(str::to_owned),
+                image: None,
+                bio: None,
+            },
+        }
+    }
+
+    #[test]
+    fn signup_with_valid_email() {
+        let config = Config::default();
+        assert!(signup("jake@jake.jake", "jakejake")
+            .validate(&config)
+            .is_ok());
+    }
+
+    #[test]
+    fn signup_with_malformed_email() {
+        let config = Config::default();
+        assert!(matches!(
+            signup("notanemail", "jakejake").validate(&config),
+            Err(AppError::UnprocessableEntity(_))
+        ));
+    }
+
+    #[test]
+    fn signup_with_too_short_password() {
+        let config = Config::default();
+        match signup("jake@jake.jake", "jake").validate(&config) {
+            Err(AppError::UnprocessableEntity(body)) => assert_eq!(
+                body,
+                json!({"errors": {"password": ["is too short (minimum is 8 characters)"]}})
+            ),
+            _ => panic!("expected unprocessable entity"),
+        }
+    }
+
+    #[test]
+    fn signin_with_exactly_one_identifier() {
+        assert_eq!(
+            signin(Some("jake@jake.jake"), None).validate().unwrap(),
+            SigninBy::Email("jake@jake.jake".to_owned())
+        );
+        assert_eq!(
+            signin(None, Some("jake")).validate().unwrap(),
+            SigninBy::Username("jake".to_owned())
+        );
+    }
+
+    #[test]
+    fn signin_with_both_or_neither_identifier() {
+        assert!(matches!(
+            signin(Some("jake@jake.jake"), Some("jake")).validate(),
+            Err(AppError::UnprocessableEntity(_))
+        ));
+        assert!(matches!(
+            signin(None, None).validate(),
+            Err(AppError::UnprocessableEntity(_))
+        ));
+    }
+
+    #[test]
+    fn update_with_too_short_password() {
+        let config = Config::default();
+        assert!(matches!(
+            update(Some("jake")).validate(&config),
+            Err(AppError::UnprocessableEntity(_))
+        ));
+        assert!(update(Some("jakejake")).validate(&config).is_ok());
+        assert!(update(None).validate(&config).is_ok());
+    }
+
+    #[test]
+    fn update_may_omit_email_and_username() {
+        assert!(update_identity(None, None, None)
+            .validate(&Config::default())
+            .is_ok());
+    }
+
+    #[test]
+    fn update_with_blank_email_or_username() {
+        match update_identity(Some(""), Some("  "), None).validate(&Config::default()) {
+            Err(AppError::UnprocessableEntity(body)) => assert_eq!(
+                body,
+                json!({"errors": {
+                    "email": ["can't be blank"],
+                    "username": ["can't be blank"],
+                }})
+            ),
+            _ => panic!("expected unprocessable entity"),
+        }
+    }
+
+    #[test]
+    fn update_with_new_email_and_username() {
+        assert!(update_identity(Some("jake@jake.jake"), Some("jake"), None)
+            .validate(&Config::default())
+            .is_ok());
+    }
 }
@@ -1,8 +1,9 @@
 use crate::app::user::model::User;
 use serde::{Deserialize, Serialize};
 use std::convert::From;
+use utoipa::ToSchema;
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
 pub struct UserResponse {
     pub user: AuthUser,
 }
@@ -22,7 +23,7 @@ impl From<(User, String)> for UserResponse {
     }
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
 pub struct AuthUser {
     pub email: String,
     pub token: String,
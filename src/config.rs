@@ -0,0 +1,273 @@
+use crate::constants::{self, env_key};
+use crate::utils::hasher;
+use std::env;
+use std::fmt::Debug;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::thread;
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub password_min_length: usize,
+    pub soft_delete: bool,
+    pub json_payload_limit: usize,
+    pub bcrypt_cost: u32,
+    pub metrics_enabled: bool,
+    pub pagination: PaginationConfig,
+    pub compression_enabled: bool,
+    pub server: ServerConfig,
+    pub image_upload_limit: usize,
+    // `None` unless `S3_BUCKET` is set, in which case image uploads are enabled.
+    pub s3: Option<S3Config>,
+    // Counting views writes to the primary on every article fetch.
+    pub track_views: bool,
+    pub token: TokenConfig,
+    pub max_tags_per_article: usize,
+    // In bytes.
+    pub max_article_body_length: usize,
+    pub slug_max_length: usize,
+    // 0 disables the limit.
+    pub user_writes_per_minute: u32,
+    // Whether the server starts in read-only mode, see `AppState::read_only`.
+    pub read_only: bool,
+    // `None` disables the admin endpoints.
+    pub admin_secret: Option<String>,
+    // Database operations taking longer than this are logged, see `AppState::timed`.
+    pub slow_query_threshold_ms: u64,
+    // False turns away `POST /api/users`, e.g. for invite-only deployments.
+    pub signup_enabled: bool,
+    // Origin the API is reachable at from outside, e.g. "https://api.example.com", for absolute
+    // `Location` headers. NOTE: `None` makes them relative to the request's origin.
+    pub public_base_url: Option<String>,
+}
+
+// The `iss` and `aud` claims tokens are issued with and must carry to be accepted.
+#[derive(Clone, Debug)]
+pub struct TokenConfig {
+    pub issuer: String,
+    pub audience: String,
+}
+
+impl Default for TokenConfig {
+    fn default() -> Self {
+        Self {
+            issuer: constants::SERVICE_NAME.to_owned(),
+            audience: constants::SERVICE_NAME.to_owned(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PaginationConfig {
+    pub default_limit: i64,
+    pub max_limit: i64,
+}
+
+#[derive(Clone, Debug)]
+pub struct S3Config {
+    pub bucket: String,
+    pub region: String,
+    pub endpoint: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    // Base URL the uploaded objects are served from.
+    pub public_url: String,
+}
+
+impl S3Config {
+    fn from_env() -> Option<Self> {
+        let bucket = env::var(env_key::S3_BUCKET).ok()?;
+        let required = |key: &str| {
+            env::var(key).unwrap_or_else(|_| panic!("{} must be set with S3_BUCKET", key))
+        };
+        let endpoint = required(env_key::S3_ENDPOINT);
+        let public_url = env::var(env_key::S3_PUBLIC_URL)
+            .unwrap_or_else(|_| format!("{}/{}", endpoint.trim_end_matches('/'), bucket));
+        Some(Self {
+            region: env::var(env_key::S3_REGION).unwrap_or_else(|_| "us-east-1".to_owned()),
+            access_key_id: required(env_key::S3_ACCESS_KEY_ID),
+            secret_access_key: required(env_key::S3_SECRET_ACCESS_KEY),
+            bucket,
+            endpoint,
+            public_url,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ServerConfig {
+    pub bind_addr: SocketAddr,
+    pub workers: usize,
+    pub keep_alive_secs: u64,
+    // `None` unless both `TLS_CERT_PATH` and `TLS_KEY_PATH` are set, in which case HTTPS is served.
+    pub tls: Option<TlsConfig>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+impl TlsConfig {
+    fn from_env() -> Option<Self> {
+        match (
+            env::var_os(env_key::TLS_CERT_PATH),
+            env::var_os(env_key::TLS_KEY_PATH),
+        ) {
+            (Some(cert_path), Some(key_path)) => Some(Self {
+                cert_path: cert_path.into(),
+                key_path: key_path.into(),
+            }),
+            (None, None) => None,
+            _ => panic!(
+                "{} and {} must be set together",
+                env_key::TLS_CERT_PATH,
+                env_key::TLS_KEY_PATH
+            ),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            password_min_length: 8,
+            soft_delete: false,
+            json_payload_limit: 256 * 1024,
+            bcrypt_cost: bcrypt::DEFAULT_COST,
+            metrics_enabled: false,
+            pagination: PaginationConfig {
+                default_limit: 20,
+                max_limit: 100,
+            },
+            compression_enabled: true,
+            server: ServerConfig {
+                bind_addr: constants::BIND
+                    .parse()
+                    .expect("invalid default bind address"),
+                workers: thread::available_parallelism().map_or(1, |n| n.get()),
+                keep_alive_secs: 5,
+                tls: None,
+            },
+            image_upload_limit: 1024 * 1024,
+            s3: None,
+            track_views: true,
+            token: TokenConfig::default(),
+            max_tags_per_article: 10,
+            max_article_body_length: 100 * 1024,
+            slug_max_length: 80,
+            user_writes_per_minute: 0,
+            read_only: false,
+            admin_secret: None,
+            slow_query_threshold_ms: 500,
+            signup_enabled: true,
+            public_base_url: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let bcrypt_cost = parse_env(env_key::BCRYPT_COST, default.bcrypt_cost);
+        assert!(
+            (hasher::MIN_COST..=hasher::MAX_COST).contains(&bcrypt_cost),
+            "{} must be between {} and {}",
+            env_key::BCRYPT_COST,
+            hasher::MIN_COST,
+            hasher::MAX_COST
+        );
+        let pagination = PaginationConfig {
+            default_limit: parse_env(
+                env_key::PAGINATION_DEFAULT_LIMIT,
+                default.pagination.default_limit,
+            ),
+            max_limit: parse_env(env_key::PAGINATION_MAX_LIMIT, default.pagination.max_limit),
+        };
+        assert!(
+            0 < pagination.default_limit && pagination.default_limit <= pagination.max_limit,
+            "{} must be positive and not greater than {}",
+            env_key::PAGINATION_DEFAULT_LIMIT,
+            env_key::PAGINATION_MAX_LIMIT
+        );
+        let server = ServerConfig {
+            bind_addr: parse_env(env_key::BIND_ADDR, default.server.bind_addr),
+            workers: parse_env(env_key::HTTP_WORKERS, default.server.workers),
+            keep_alive_secs: parse_env(
+                env_key::HTTP_KEEPALIVE_SECS,
+                default.server.keep_alive_secs,
+            ),
+            tls: TlsConfig::from_env(),
+        };
+        assert!(
+            server.workers > 0,
+            "{} must be positive",
+            env_key::HTTP_WORKERS
+        );
+        Self {
+            password_min_length: parse_env(
+                env_key::PASSWORD_MIN_LENGTH,
+                default.password_min_length,
+            ),
+            soft_delete: parse_env(env_key::SOFT_DELETE, default.soft_delete),
+            json_payload_limit: parse_env(env_key::JSON_PAYLOAD_LIMIT, default.json_payload_limit),
+            bcrypt_cost,
+            metrics_enabled: parse_env(env_key::METRICS_ENABLED, default.metrics_enabled),
+            pagination,
+            compression_enabled: parse_env(
+                env_key::COMPRESSION_ENABLED,
+                default.compression_enabled,
+            ),
+            server,
+            image_upload_limit: parse_env(env_key::IMAGE_UPLOAD_LIMIT, default.image_upload_limit),
+            s3: S3Config::from_env(),
+            track_views: parse_env(env_key::TRACK_VIEWS, default.track_views),
+            token: TokenConfig {
+                issuer: parse_env(env_key::JWT_ISSUER, default.token.issuer),
+                audience: parse_env(env_key::JWT_AUDIENCE, default.token.audience),
+            },
+            max_tags_per_article: parse_env(
+                env_key::MAX_TAGS_PER_ARTICLE,
+                default.max_tags_per_article,
+            ),
+            max_article_body_length: parse_env(
+                env_key::MAX_ARTICLE_BODY_LENGTH,
+                default.max_article_body_length,
+            ),
+            slug_max_length: parse_env(env_key::SLUG_MAX_LENGTH, default.slug_max_length),
+            user_writes_per_minute: parse_env(
+                env_key::USER_WRITES_PER_MINUTE,
+                default.user_writes_per_minute,
+            ),
+            read_only: parse_env(env_key::READ_ONLY, default.read_only),
+            admin_secret: env::var(env_key::ADMIN_SECRET)
+                .ok()
+                .filter(|secret| !secret.is_empty()),
+            slow_query_threshold_ms: parse_env(
+                env_key::SLOW_QUERY_THRESHOLD_MS,
+                default.slow_query_threshold_ms,
+            ),
+            signup_enabled: parse_env(env_key::SIGNUP_ENABLED, default.signup_enabled),
+            public_base_url: env::var(env_key::PUBLIC_BASE_URL)
+                .ok()
+                .map(|url| url.trim_end_matches('/').to_owned())
+                .filter(|url| !url.is_empty()),
+        }
+    }
+}
+
+// Fails fast on startup when a value is set but cannot be parsed.
+fn parse_env<T>(key: &str, default: T) -> T
+where
+    T: FromStr,
+    T::Err: Debug,
+{
+    match env::var(key) {
+        Ok(value) => value
+            .parse()
+            .unwrap_or_else(|err| panic!("{} is invalid: {:?}", key, err)),
+        Err(_) => default,
+    }
+}
@@ -1,8 +1,65 @@
 pub const AUTHORIZATION: &str = "Authorization";
 
+pub mod header {
+    pub const X_TOTAL_COUNT: &str = "X-Total-Count";
+    pub const X_LIMIT: &str = "X-Limit";
+    pub const X_OFFSET: &str = "X-Offset";
+    pub const X_NEXT_CURSOR: &str = "X-Next-Cursor";
+    pub const X_REQUEST_ID: &str = "X-Request-Id";
+    pub const IDEMPOTENCY_KEY: &str = "Idempotency-Key";
+    pub const X_ADMIN_SECRET: &str = "X-Admin-Secret";
+}
+
+// Default for `BIND_ADDR`.
 pub const BIND: &str = "0.0.0.0:8080";
 
+// Seconds to wait for in-flight requests to finish after a shutdown signal.
+pub const SHUTDOWN_TIMEOUT: u64 = 30;
+
+// Attempts of a transaction run through `utils::db::with_retry`.
+pub const TRANSACTION_ATTEMPTS: usize = 3;
+
+// Hours an `Idempotency-Key` keeps answering with the response it was first used for.
+pub const IDEMPOTENCY_KEY_TTL_HOURS: i64 = 24;
+
+// Default for `JWT_ISSUER` and `JWT_AUDIENCE`.
+pub const SERVICE_NAME: &str = "conduit";
+
 pub mod env_key {
     pub const DATABASE_URL: &str = "DATABASE_URL";
+    pub const TEST_DATABASE_URL: &str = "TEST_DATABASE_URL";
+    pub const REPLICA_DATABASE_URL: &str = "REPLICA_DATABASE_URL";
     pub const FRONTEND_ORIGIN: &str = "FRONTEND_ORIGIN";
+    pub const PASSWORD_MIN_LENGTH: &str = "PASSWORD_MIN_LENGTH";
+    pub const SOFT_DELETE: &str = "SOFT_DELETE";
+    pub const JSON_PAYLOAD_LIMIT: &str = "JSON_PAYLOAD_LIMIT";
+    pub const BCRYPT_COST: &str = "BCRYPT_COST";
+    pub const METRICS_ENABLED: &str = "METRICS_ENABLED";
+    pub const PAGINATION_DEFAULT_LIMIT: &str = "PAGINATION_DEFAULT_LIMIT";
+    pub const PAGINATION_MAX_LIMIT: &str = "PAGINATION_MAX_LIMIT";
+    pub const COMPRESSION_ENABLED: &str = "COMPRESSION_ENABLED";
+    pub const TRACK_VIEWS: &str = "TRACK_VIEWS";
+    pub const MAX_TAGS_PER_ARTICLE: &str = "MAX_TAGS_PER_ARTICLE";
+    pub const MAX_ARTICLE_BODY_LENGTH: &str = "MAX_ARTICLE_BODY_LENGTH";
+    pub const SLUG_MAX_LENGTH: &str = "SLUG_MAX_LENGTH";
+    pub const USER_WRITES_PER_MINUTE: &str = "USER_WRITES_PER_MINUTE";
+    pub const READ_ONLY: &str = "READ_ONLY";
+    pub const ADMIN_SECRET: &str = "ADMIN_SECRET";
+    pub const SLOW_QUERY_THRESHOLD_MS: &str = "SLOW_QUERY_THRESHOLD_MS";
+    pub const SIGNUP_ENABLED: &str = "SIGNUP_ENABLED";
+    pub const PUBLIC_BASE_URL: &str = "PUBLIC_BASE_URL";
+    pub const JWT_ISSUER: &str = "JWT_ISSUER";
+    pub const JWT_AUDIENCE: &str = "JWT_AUDIENCE";
+    pub const BIND_ADDR: &str = "BIND_ADDR";
+    pub const TLS_CERT_PATH: &str = "TLS_CERT_PATH";
+    pub const TLS_KEY_PATH: &str = "TLS_KEY_PATH";
+    pub const HTTP_WORKERS: &str = "HTTP_WORKERS";
+    pub const HTTP_KEEPALIVE_SECS: &str = "HTTP_KEEPALIVE_SECS";
+    pub const IMAGE_UPLOAD_LIMIT: &str = "IMAGE_UPLOAD_LIMIT";
+    pub const S3_BUCKET: &str = "S3_BUCKET";
+    pub const S3_REGION: &str = "S3_REGION";
+    pub const S3_ENDPOINT: &str = "S3_ENDPOINT";
+    pub const S3_ACCESS_KEY_ID: &str = "S3_ACCESS_KEY_ID";
+    pub const S3_SECRET_ACCESS_KEY: &str = "S3_SECRET_ACCESS_KEY";
+    pub const S3_PUBLIC_URL: &str = "S3_PUBLIC_URL";
 }
@@ -1,3 +1,4 @@
+use crate::middleware::error::ErrorResponse;
 use actix_web::{http::StatusCode, HttpResponse};
 use bcrypt::BcryptError;
 use diesel::r2d2::{Error as R2D2Error, PoolError};
@@ -11,6 +12,10 @@ use uuid::Error as UuidError;
 
 #[derive(Error, Debug)]
 pub enum AppError {
+    // 400
+    #[error("Bad Request: {}", _0)]
+    BadRequest(JsonValue),
+
     // 401
     #[error("Unauthorized: {}", _0)]
     Unauthorized(JsonValue),
@@ -23,6 +28,18 @@ pub enum AppError {
     #[error("Not Found: {}", _0)]
     NotFound(JsonValue),
 
+    // 409
+    #[error("Conflict: {}", _0)]
+    Conflict(JsonValue),
+
+    // 413
+    #[error("Payload Too Large: {}", _0)]
+    PayloadTooLarge(JsonValue),
+
+    // 415
+    #[error("Unsupported Media Type: {}", _0)]
+    UnsupportedMediaType(JsonValue),
+
     // 422
     #[error("Unprocessable Entity: {}", _0)]
     UnprocessableEntity(JsonValue),
@@ -30,27 +47,43 @@ pub enum AppError {
     // 500
     #[error("Internal Server Error")]
     InternalServerError,
+
+    // 501
+    #[error("Not Implemented: {}", _0)]
+    NotImplemented(JsonValue),
 }
 
 impl actix_web::error::ResponseError for AppError {
     fn error_response(&self) -> HttpResponse {
         match self {
+            AppError::BadRequest(ref msg) => HttpResponse::BadRequest().json(msg),
             AppError::Unauthorized(ref msg) => HttpResponse::Unauthorized().json(msg),
             AppError::Forbidden(ref msg) => HttpResponse::Forbidden().json(msg),
             AppError::NotFound(ref msg) => HttpResponse::NotFound().json(msg),
+            AppError::Conflict(ref msg) => HttpResponse::Conflict().json(msg),
+            AppError::PayloadTooLarge(ref msg) => HttpResponse::PayloadTooLarge().json(msg),
+            AppError::UnsupportedMediaType(ref msg) => {
+                HttpResponse::UnsupportedMediaType().json(msg)
+            }
             AppError::UnprocessableEntity(ref msg) => HttpResponse::UnprocessableEntity().json(msg),
             AppError::InternalServerError => {
                 HttpResponse::InternalServerError().json("Internal Server Error")
             }
+            AppError::NotImplemented(ref msg) => HttpResponse::NotImplemented().json(msg),
         }
     }
     fn status_code(&self) -> StatusCode {
         match *self {
+            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
             AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
             AppError::Forbidden(_) => StatusCode::FORBIDDEN,
             AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            AppError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
             AppError::UnprocessableEntity(_) => StatusCode::UNPROCESSABLE_ENTITY,
             AppError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::NotImplemented(_) => StatusCode::NOT_IMPLEMENTED,
         }
     }
 }
@@ -61,13 +94,18 @@ impl From<PoolError> for AppError {
     }
 }
 
+// A wrong password is `Ok(false)` from `verify`, not an error. Errors other than an invalid
+// password mean the stored hash is unreadable, which is a server-side problem.
 impl From<BcryptError> for AppError {
     fn from(err: BcryptError) -> Self {
         match err {
-            BcryptError::InvalidPassword => AppError::Unauthorized(json!({
-                "error": "PW is invalid"
-            })),
-            _ => AppError::InternalServerError,
+            BcryptError::InvalidPassword => {
+                AppError::Unauthorized(json!(ErrorResponse::from("PW is invalid")))
+            }
+            _ => {
+                error!("Cannot verify password: {}", err);
+                AppError::InternalServerError
+            }
         }
     }
 }
@@ -75,15 +113,15 @@ impl From<BcryptError> for AppError {
 impl From<JwtError> for AppError {
     fn from(err: JwtError) -> Self {
         match err.kind() {
-            JwtErrorKind::InvalidToken => AppError::Unauthorized(json!({
-                "error": "Token is invalid"
-            })),
-            JwtErrorKind::InvalidIssuer => AppError::Unauthorized(json!({
-                "error": "Issuer is invalid",
-            })),
-            _ => AppError::Unauthorized(json!({
-                "error": "An issue was found with the token provided",
-            })),
+            JwtErrorKind::InvalidToken => {
+                AppError::Unauthorized(json!(ErrorResponse::from("Token is invalid")))
+            }
+            JwtErrorKind::InvalidIssuer => {
+                AppError::Unauthorized(json!(ErrorResponse::from("Issuer is invalid")))
+            }
+            _ => AppError::Unauthorized(json!(ErrorResponse::from(
+                "An issue was found with the token provided"
+            ))),
         }
     }
 }
@@ -100,21 +138,32 @@ impl From<DieselError> for AppError {
             DieselError::DatabaseError(kind, info) => {
                 if let DatabaseErrorKind::UniqueViolation = kind {
                     let message = info.details().unwrap_or_else(|| info.message()).to_string();
-                    AppError::UnprocessableEntity(json!({ "error": message }))
+                    AppError::UnprocessableEntity(json!(ErrorResponse::from(message.as_str())))
+                } else if is_retryable(&kind, info.message()) {
+                    AppError::Conflict(json!(ErrorResponse::from("conflicting concurrent update")))
                 } else {
                     AppError::InternalServerError
                 }
             }
             DieselError::NotFound => {
-                AppError::NotFound(json!({ "error": "requested record was not found" }))
+                AppError::NotFound(json!(ErrorResponse::from("requested record was not found")))
             }
             _ => AppError::InternalServerError,
         }
     }
 }
 
+// Serialization failures (40001) and deadlocks (40P01) abort a transaction which would succeed
+// if run again. Diesel has no kind for deadlocks, so they are told apart by the message.
+// NOTE: Diesel 1.4 doesn't expose the SQLSTATE, and the message follows the `lc_messages` of the
+// server. With anything but English messages, deadlocks are not retried and answer 500.
+fn is_retryable(kind: &DatabaseErrorKind, message: &str) -> bool {
+    matches!(kind, DatabaseErrorKind::SerializationFailure)
+        || message.starts_with("deadlock detected")
+}
+
 impl From<UuidError> for AppError {
     fn from(_err: UuidError) -> Self {
-        AppError::NotFound(json!({"error":"Uuid is invalid."}))
+        AppError::NotFound(json!(ErrorResponse::from("Uuid is invalid.")))
     }
 }
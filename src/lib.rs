@@ -0,0 +1,15 @@
+#[macro_use]
+extern crate diesel;
+
+#[macro_use]
+extern crate log;
+
+pub mod app;
+pub mod config;
+pub mod constants;
+pub mod error;
+pub mod middleware;
+pub mod routes;
+pub mod schema;
+pub mod seed;
+pub mod utils;
@@ -1,39 +1,142 @@
-#[macro_use]
-extern crate diesel;
-
 #[macro_use]
 extern crate log;
 
-use actix_web::middleware::Logger;
+use actix_web::middleware::{Compress, Condition, Logger};
 use actix_web::{App, HttpServer};
-mod app;
-mod constants;
-mod error;
-mod middleware;
-mod routes;
-mod schema;
-mod utils;
+use clap::{Parser, Subcommand};
+use conduit::{config, constants, middleware, routes, seed, utils};
+use env_logger::Env;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(about = "Realworld Application with Rust / actix-web / diesel.")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the API server (default)
+    Serve,
+    /// Populate the database with demo users, articles, tags and comments
+    Seed,
+}
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    println!("start conduit server...");
-    std::env::set_var("RUST_LOG", "actix_web=trace");
-    env_logger::init();
+    let cli = Cli::parse();
+    // `RUST_LOG` still overrides this, e.g. `RUST_LOG=conduit=debug,actix_web=trace`.
+    env_logger::Builder::from_env(Env::default().default_filter_or("conduit=info,actix_web=info"))
+        .init();
+
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => serve().await,
+        Command::Seed => {
+            seed_db();
+            Ok(())
+        }
+    }
+}
+
+fn seed_db() {
+    info!("Seeding database...");
+    let pool = utils::db::establish_connection();
+    let config = config::Config::from_env();
+    let conn = pool.get().expect("Failed to get db connection");
+    seed::run(&conn, &config).expect("Failed to seed database");
+    info!("Seed completed.");
+}
 
+async fn serve() -> std::io::Result<()> {
+    println!("start conduit server...");
     let state = {
         let pool = utils::db::establish_connection();
-        middleware::state::AppState { pool }
+        let replica_pool = utils::db::establish_replica_connection();
+        let config = config::Config::from_env();
+        let metrics = middleware::metrics::Metrics::new();
+        let rate_limiter = middleware::rate_limit::RateLimiter::new();
+        let read_only = Arc::new(AtomicBool::new(config.read_only));
+        middleware::state::AppState {
+            pool,
+            replica_pool,
+            config,
+            metrics,
+            rate_limiter,
+            read_only,
+        }
     };
 
-    HttpServer::new(move || {
-        App::new()
-            .wrap(Logger::default())
-            .app_data(actix_web::web::Data::new(state.clone()))
-            .wrap(middleware::cors::cors())
-            .wrap(middleware::auth::Authentication)
-            .configure(routes::api)
-    })
-    .bind(constants::BIND)?
-    .run()
-    .await
+    let server_config = state.config.server.clone();
+    let tls_config = server_config
+        .tls
+        .as_ref()
+        .map(utils::tls::load_server_config)
+        .transpose()?;
+    let server = {
+        let state = state.clone();
+        let server = HttpServer::new(move || {
+            App::new()
+                .wrap(middleware::localize::Localize)
+                .wrap(Logger::new(middleware::request_id::LOG_FORMAT))
+                .wrap(Condition::new(
+                    state.config.compression_enabled,
+                    middleware::compress::SkipSmallBodies,
+                ))
+                .wrap(Condition::new(
+                    state.config.compression_enabled,
+                    Compress::default(),
+                ))
+                .app_data(actix_web::web::Data::new(state.clone()))
+                .app_data(middleware::json::json_config(
+                    state.config.json_payload_limit,
+                ))
+                .wrap(middleware::cors::cors())
+                .wrap(middleware::auth::Authentication)
+                .wrap(middleware::read_only::ReadOnly)
+                .wrap(middleware::catch_panic::CatchPanic)
+                .wrap(middleware::metrics::RequestMetrics)
+                .wrap(middleware::request_id::RequestId)
+                .configure(routes::api)
+        });
+        let server = match tls_config {
+            Some(tls_config) => server.bind_rustls(server_config.bind_addr, tls_config)?,
+            None => server.bind(server_config.bind_addr)?,
+        };
+        server
+            .workers(server_config.workers)
+            .keep_alive(Duration::from_secs(server_config.keep_alive_secs))
+            .shutdown_timeout(constants::SHUTDOWN_TIMEOUT)
+            .disable_signals()
+            .run()
+    };
+
+    let handle = server.handle();
+    actix_web::rt::spawn(async move {
+        shutdown_signal().await;
+        info!("Shutdown signal received, draining in-flight requests...");
+        handle.stop(true).await;
+    });
+
+    server.await?;
+
+    // Workers are stopped here, so this is the last reference to the pool.
+    drop(state);
+    info!("Shutdown completed.");
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn shutdown_signal() {
+    use actix_web::rt::signal::{ctrl_c, unix};
+    let mut sigterm =
+        unix::signal(unix::SignalKind::terminate()).expect("Failed to install SIGTERM handler");
+    futures::future::select(Box::pin(ctrl_c()), Box::pin(sigterm.recv())).await;
+}
+
+#[cfg(not(unix))]
+async fn shutdown_signal() {
+    let _ = actix_web::rt::signal::ctrl_c().await;
 }
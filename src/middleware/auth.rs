@@ -1,6 +1,8 @@
 use crate::app::user::model::User;
+use crate::config::TokenConfig;
 use crate::constants;
 use crate::error::AppError;
+use crate::middleware::error::ErrorResponse;
 use crate::middleware::state::AppState;
 use crate::utils::token;
 use actix_web::HttpMessage;
@@ -9,7 +11,7 @@ use actix_web::{
     dev::{Service, ServiceRequest, ServiceResponse, Transform},
     http::Method,
     web::Data,
-    Error, HttpRequest, HttpResponse,
+    Error, HttpRequest, ResponseError,
 };
 use diesel::pg::PgConnection;
 use futures::future::{ok, Ready};
@@ -63,24 +65,30 @@ where
     actix_web::dev::forward_ready!(service);
 
     fn call(&self, mut req: ServiceRequest) -> Self::Future {
-        let is_verified = if should_skip_auth(&req) {
-            true
+        let verified = if should_skip_auth(&req) {
+            // Public routes still pick up the user when a token is given,
+            // so that following/favorited can be resolved for the viewer.
+            if req.headers().contains_key(constants::AUTHORIZATION) {
+                let _ = set_auth_user(&mut req);
+            }
+            Ok(())
         } else {
             set_auth_user(&mut req)
         };
-        if is_verified {
-            let fut = self.service.call(req);
-            Box::pin(async move {
-                let res = fut.await?.map_into_left_body();
-                Ok(res)
-            })
-        } else {
-            Box::pin(async move {
+        match verified {
+            Ok(()) => {
+                let fut = self.service.call(req);
+                Box::pin(async move {
+                    let res = fut.await?.map_into_left_body();
+                    Ok(res)
+                })
+            }
+            Err(err) => Box::pin(async move {
                 let (req, _res) = req.into_parts();
-                let res = HttpResponse::Unauthorized().finish().map_into_right_body();
+                let res = err.error_response().map_into_right_body();
                 let srv = ServiceResponse::new(req, res);
                 Ok(srv)
-            })
+            }),
         }
     }
 }
@@ -91,6 +99,10 @@ fn should_skip_auth(req: &ServiceRequest) -> bool {
         return true;
     }
 
+    if req.match_pattern().is_none() {
+        return true;
+    }
+
     SKIP_AUTH_ROUTES
         .iter()
         .any(|route| route.matches_path_and_method(req.path(), req.method()))
@@ -98,45 +110,60 @@ fn should_skip_auth(req: &ServiceRequest) -> bool {
 
 const TOKEN_IDENTIFIER: &str = "Token";
 
-fn set_auth_user(req: &mut ServiceRequest) -> bool {
+fn set_auth_user(req: &mut ServiceRequest) -> Result<(), AppError> {
     match fetch_user(req) {
         Ok(user) => {
             req.extensions_mut().insert(user);
-            true
+            Ok(())
         }
-        Err(err_msg) => {
-            info!("Cannot fetch user {}", err_msg);
-            false
+        Err(err) => {
+            info!("Cannot fetch user {}", err);
+            Err(err)
         }
     }
 }
 
-fn fetch_user(req: &ServiceRequest) -> Result<User, &str> {
-    let user_id = get_user_id_from_header(req)?;
-
-    let conn = req
+fn fetch_user(req: &ServiceRequest) -> Result<User, AppError> {
+    let state = req
         .app_data::<Data<AppState>>()
-        .ok_or("Cannot get state.")
-        .and_then(|state| state.get_conn().map_err(|_err| "Cannot get db connection."))?;
+        .ok_or(AppError::InternalServerError)?;
+
+    let user_id = get_user_id_from_header(req, &state.config.token)?;
 
-    find_auth_user(&conn, user_id).map_err(|_err| "Cannot find auth user")
+    let conn = state.write_conn()?;
+
+    // A valid token may still outlive its user (e.g. after account deletion).
+    find_auth_user(&conn, user_id).map_err(|err| match err {
+        AppError::NotFound(_) => invalid_token(),
+        err => err,
+    })
 }
 
-fn get_user_id_from_header(req: &ServiceRequest) -> Result<Uuid, &str> {
-    req.headers()
-        .get(constants::AUTHORIZATION)
-        .ok_or("Cannot find authrization key-value in req header")
-        .and_then(|auth_header| auth_header.to_str().map_err(|_err| "Cannot stringify"))
-        .and_then(|auth_str| {
-            if auth_str.starts_with(TOKEN_IDENTIFIER) {
-                Ok(auth_str)
-            } else {
-                Err("Invalid token convention")
-            }
-        })
-        .map(|auth_str| auth_str[6..auth_str.len()].trim())
-        .and_then(|token| token::decode(token).map_err(|_err| "Cannot decode token."))
+// A missing header and a bad token are 401, so the client should (re)authenticate, while a
+// header that isn't `Token <jwt>` at all is a client bug and 400.
+fn get_user_id_from_header(
+    req: &ServiceRequest,
+    token_config: &TokenConfig,
+) -> Result<Uuid, AppError> {
+    let auth_header = req.headers().get(constants::AUTHORIZATION).ok_or_else(|| {
+        AppError::Unauthorized(json!(ErrorResponse::from("missing authorization")))
+    })?;
+    let token = auth_header
+        .to_str()
+        .ok()
+        .and_then(|auth_str| auth_str.trim().split_once(' '))
+        .filter(|(scheme, token)| *scheme == TOKEN_IDENTIFIER && !token.trim().is_empty())
+        .map(|(_, token)| token.trim())
+        .ok_or_else(|| {
+            AppError::BadRequest(json!(ErrorResponse::from("invalid authorization header")))
+        })?;
+    token::decode(token, token_config)
         .map(|token| token.claims.user_id)
+        .map_err(|_err| invalid_token())
+}
+
+fn invalid_token() -> AppError {
+    AppError::Unauthorized(json!(ErrorResponse::from("invalid token")))
 }
 
 pub fn get_current_user(req: &HttpRequest) -> Result<User, AppError> {
@@ -144,7 +171,9 @@ pub fn get_current_user(req: &HttpRequest) -> Result<User, AppError> {
         .get::<User>()
         .map(|user| user.to_owned()) // TODO: avoid copy
         .ok_or_else(|| {
-            AppError::Unauthorized(json!({"error": "Unauthrized user. Need auth token on header."}))
+            AppError::Unauthorized(json!(ErrorResponse::from(
+                "Unauthrized user. Need auth token on header."
+            )))
         })
 }
 
@@ -207,15 +236,31 @@ mod tests {
     }
 }
 
-const SKIP_AUTH_ROUTES: [SkipAuthRoute; 6] = [
+const SKIP_AUTH_ROUTES: [SkipAuthRoute; 14] = [
     SkipAuthRoute {
         path: "/api/healthcheck",
         method: Method::GET,
     },
+    SkipAuthRoute {
+        path: "/api/admin/read-only",
+        method: Method::PUT,
+    },
+    SkipAuthRoute {
+        path: "/api-docs/openapi.json",
+        method: Method::GET,
+    },
+    SkipAuthRoute {
+        path: "/metrics",
+        method: Method::GET,
+    },
     SkipAuthRoute {
         path: "/api/tags",
         method: Method::GET,
     },
+    SkipAuthRoute {
+        path: "/api/tags/popular",
+        method: Method::GET,
+    },
     SkipAuthRoute {
         path: "/api/users",
         method: Method::POST,
@@ -228,10 +273,26 @@ const SKIP_AUTH_ROUTES: [SkipAuthRoute; 6] = [
         path: "/api/articles",
         method: Method::GET,
     },
+    SkipAuthRoute {
+        path: "/api/articles/tag/{tag_name}",
+        method: Method::GET,
+    },
     SkipAuthRoute {
         path: "/api/articles/{article_title_slug}/comments",
         method: Method::GET,
     },
+    SkipAuthRoute {
+        path: "/api/profiles/{username}/articles",
+        method: Method::GET,
+    },
+    SkipAuthRoute {
+        path: "/api/profiles/{username}/followers",
+        method: Method::GET,
+    },
+    SkipAuthRoute {
+        path: "/api/profiles/{username}/following",
+        method: Method::GET,
+    },
 ];
 
 // ================
@@ -0,0 +1,124 @@
+use crate::middleware::error::ErrorResponse;
+use crate::middleware::request_id;
+use actix_web::{
+    body::EitherBody,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    Error, HttpResponse,
+};
+use futures::future::{ok, Ready};
+use futures::{Future, FutureExt};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+
+// Turns a panic anywhere below it into a 500 with the usual error envelope, instead of the
+// connection being dropped without a response.
+// Wrap it inside `RequestId`, so the request id is known when the panic is logged, and
+// inside `RequestMetrics`, so the 500 is counted like any other.
+pub struct CatchPanic;
+
+impl<S, B> Transform<S, ServiceRequest> for CatchPanic
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = CatchPanicMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(CatchPanicMiddleware { service })
+    }
+}
+
+pub struct CatchPanicMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for CatchPanicMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let http_req = req.request().clone();
+        let fut = panic::catch_unwind(AssertUnwindSafe(|| self.service.call(req)));
+        Box::pin(async move {
+            let result = match fut {
+                Ok(fut) => AssertUnwindSafe(fut).catch_unwind().await,
+                Err(payload) => Err(payload),
+            };
+            match result {
+                Ok(res) => Ok(res?.map_into_left_body()),
+                Err(payload) => {
+                    error!(
+                        "Handler panicked: {} (request_id={})",
+                        panic_message(&*payload),
+                        request_id::get_request_id(&http_req).unwrap_or_default()
+                    );
+                    let res = HttpResponse::InternalServerError()
+                        .json(ErrorResponse::from("internal server error"))
+                        .map_into_right_body();
+                    Ok(ServiceResponse::new(http_req, res))
+                }
+            }
+        })
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic payload"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test, web, App};
+    use serde_json::{json, Value as JsonValue};
+
+    async fn panics() -> HttpResponse {
+        panic!("boom")
+    }
+
+    async fn ok() -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_web::test]
+    async fn panic_is_internal_server_error() {
+        let app = test::init_service(
+            App::new()
+                .wrap(CatchPanic)
+                .route("/panic", web::get().to(panics))
+                .route("/ok", web::get().to(ok)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/panic").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body: JsonValue = test::read_body_json(res).await;
+        assert_eq!(body, json!({"errors": {"body": ["internal server error"]}}));
+
+        let req = test::TestRequest::get().uri("/ok").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,123 @@
+use actix_web::{
+    body::{BodySize, MessageBody},
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{self, HeaderValue},
+    Error,
+};
+use futures::future::{ok, Ready};
+use futures::Future;
+use std::pin::Pin;
+
+// Below this size the gzip/brotli framing costs about as much as it saves.
+pub const MIN_COMPRESS_SIZE: u64 = 1024;
+
+// Marks small responses with `Content-Encoding: identity`, which tells actix's `Compress`
+// (wrapped outside of this middleware) to send them as is.
+pub struct SkipSmallBodies;
+
+impl<S, B> Transform<S, ServiceRequest> for SkipSmallBodies
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = SkipSmallBodiesMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(SkipSmallBodiesMiddleware { service })
+    }
+}
+
+pub struct SkipSmallBodiesMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for SkipSmallBodiesMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let mut res = fut.await?;
+            let is_small = matches!(
+                res.response().body().size(),
+                BodySize::Sized(size) if size < MIN_COMPRESS_SIZE
+            );
+            if is_small && !res.headers().contains_key(header::CONTENT_ENCODING) {
+                res.headers_mut().insert(
+                    header::CONTENT_ENCODING,
+                    HeaderValue::from_static("identity"),
+                );
+            }
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::middleware::Compress;
+    use actix_web::{test, web, App, HttpResponse};
+
+    async fn small() -> HttpResponse {
+        HttpResponse::Ok().body("OK")
+    }
+
+    async fn large() -> HttpResponse {
+        HttpResponse::Ok().body("article ".repeat(1024))
+    }
+
+    #[actix_web::test]
+    async fn large_response_is_compressed() {
+        let app = test::init_service(
+            App::new()
+                .wrap(SkipSmallBodies)
+                .wrap(Compress::default())
+                .route("/", web::get().to(large)),
+        )
+        .await;
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header((header::ACCEPT_ENCODING, "gzip"))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+
+        assert_eq!(res.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip");
+    }
+
+    #[actix_web::test]
+    async fn small_response_is_not_compressed() {
+        let app = test::init_service(
+            App::new()
+                .wrap(SkipSmallBodies)
+                .wrap(Compress::default())
+                .route("/", web::get().to(small)),
+        )
+        .await;
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header((header::ACCEPT_ENCODING, "gzip"))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+
+        assert_ne!(res.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip");
+        assert_eq!(test::read_body(res).await, "OK");
+    }
+}
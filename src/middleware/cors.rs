@@ -1,4 +1,4 @@
-use crate::constants::env_key;
+use crate::constants::{env_key, header};
 use actix_cors::Cors;
 use actix_web::http;
 use std::env;
@@ -11,5 +11,13 @@ pub fn cors() -> Cors {
         .allowed_origin_fn(|origin, _req_head| origin.as_bytes().ends_with(b".rust-lang.org"))
         .allowed_headers(vec![http::header::AUTHORIZATION, http::header::ACCEPT])
         .allowed_header(http::header::CONTENT_TYPE)
+        .expose_headers(vec![
+            header::X_TOTAL_COUNT,
+            header::X_LIMIT,
+            header::X_OFFSET,
+            header::X_NEXT_CURSOR,
+            header::X_REQUEST_ID,
+            http::header::LOCATION.as_str(),
+        ])
         .max_age(3600)
 }
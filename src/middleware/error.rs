@@ -1,22 +1,18 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::convert::From;
+use utoipa::ToSchema;
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, ToSchema)]
 pub struct ErrorResponse {
-    pub errors: Inner,
+    // Messages per field, or under `body` for errors not tied to a field.
+    pub errors: BTreeMap<String, Vec<String>>,
 }
 
 impl From<&str> for ErrorResponse {
     fn from(msg: &str) -> Self {
         Self {
-            errors: Inner {
-                body: vec![msg.to_owned()],
-            },
+            errors: BTreeMap::from([("body".to_owned(), vec![msg.to_owned()])]),
         }
     }
 }
-
-#[derive(Deserialize, Serialize)]
-pub struct Inner {
-    body: Vec<String>,
-}
@@ -0,0 +1,143 @@
+use crate::error::AppError;
+use crate::middleware::error::ErrorResponse;
+use actix_web::error::JsonPayloadError;
+use actix_web::web::JsonConfig;
+use serde_json::json;
+
+// Serde messages quote the offending input, which can be arbitrarily long.
+const MAX_JSON_ERROR_DETAIL_LENGTH: usize = 200;
+
+pub fn json_config(limit: usize) -> JsonConfig {
+    JsonConfig::default()
+        .limit(limit)
+        .error_handler(|err, _req| match err {
+            JsonPayloadError::Overflow { .. } | JsonPayloadError::OverflowKnownLength { .. } => {
+                AppError::PayloadTooLarge(json!(ErrorResponse::from("payload too large"))).into()
+            }
+            JsonPayloadError::ContentType => AppError::UnsupportedMediaType(json!(
+                ErrorResponse::from("content type must be application/json")
+            ))
+            .into(),
+            JsonPayloadError::Deserialize(err) => {
+                let message = format!("invalid JSON: {}", truncate(&err.to_string()));
+                AppError::UnprocessableEntity(json!(ErrorResponse::from(message.as_str()))).into()
+            }
+            _ => err.into(),
+        })
+}
+
+fn truncate(detail: &str) -> &str {
+    match detail.char_indices().nth(MAX_JSON_ERROR_DETAIL_LENGTH) {
+        Some((end, _)) => &detail[..end],
+        None => detail,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test, web, App, HttpResponse};
+    use serde_json::Value as JsonValue;
+
+    async fn echo(body: web::Json<JsonValue>) -> HttpResponse {
+        HttpResponse::Ok().json(body.into_inner())
+    }
+
+    #[actix_web::test]
+    async fn too_large_payload_is_rejected() {
+        let app = test::init_service(
+            App::new()
+                .app_data(json_config(16))
+                .route("/", web::post().to(echo)),
+        )
+        .await;
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(json!({"body": "this body is longer than the limit"}))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body: JsonValue = test::read_body_json(res).await;
+        assert_eq!(body, json!({"errors": {"body": ["payload too large"]}}));
+    }
+
+    #[actix_web::test]
+    async fn non_json_content_type_is_rejected() {
+        let app = test::init_service(
+            App::new()
+                .app_data(json_config(1024))
+                .route("/", web::post().to(echo)),
+        )
+        .await;
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(("content-type", "text/plain"))
+            .set_payload(r#"{"body": "ok"}"#)
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        let body: JsonValue = test::read_body_json(res).await;
+        assert_eq!(
+            body,
+            json!({"errors": {"body": ["content type must be application/json"]}})
+        );
+    }
+
+    #[actix_web::test]
+    async fn payload_within_limit_is_accepted() {
+        let app = test::init_service(
+            App::new()
+                .app_data(json_config(1024))
+                .route("/", web::post().to(echo)),
+        )
+        .await;
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(json!({"body": "ok"}))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn malformed_json_is_unprocessable() {
+        let app = test::init_service(
+            App::new()
+                .app_data(json_config(1024))
+                .route("/", web::post().to(echo)),
+        )
+        .await;
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(("content-type", "application/json"))
+            .set_payload(r#"{"body": "ok",}"#)
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body: JsonValue = test::read_body_json(res).await;
+        let message = body["errors"]["body"][0].as_str().unwrap();
+        assert!(
+            message.starts_with("invalid JSON: trailing comma"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn truncate_keeps_short_details_and_cuts_long_ones() {
+        assert_eq!(truncate("expected value"), "expected value");
+        let long = "é".repeat(MAX_JSON_ERROR_DETAIL_LENGTH + 10);
+        assert_eq!(
+            truncate(&long).chars().count(),
+            MAX_JSON_ERROR_DETAIL_LENGTH
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use crate::error::AppError;
+use crate::utils::i18n::{self, Lang};
+use actix_web::{
+    body::EitherBody,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::ACCEPT_LANGUAGE,
+    Error, ResponseError,
+};
+use futures::future::{ok, Ready};
+use futures::Future;
+use std::pin::Pin;
+
+// Translates validation messages (422 `AppError::UnprocessableEntity`) into the language asked for
+// by `Accept-Language`, see `utils::i18n`. Wrapped innermost, so it sees the handler's error
+// before any other middleware adds headers to the response.
+pub struct Localize;
+
+impl<S, B> Transform<S, ServiceRequest> for Localize
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = LocalizeMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(LocalizeMiddleware { service })
+    }
+}
+
+pub struct LocalizeMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for LocalizeMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let lang = Lang::from_accept_language(
+            req.headers()
+                .get(ACCEPT_LANGUAGE)
+                .and_then(|value| value.to_str().ok()),
+        );
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let res = fut.await?;
+            if lang == Lang::En {
+                return Ok(res.map_into_left_body());
+            }
+            let mut body = match res
+                .response()
+                .error()
+                .and_then(|err| err.as_error::<AppError>())
+            {
+                Some(AppError::UnprocessableEntity(body)) => body.clone(),
+                _ => return Ok(res.map_into_left_body()),
+            };
+            i18n::translate_errors(&mut body, lang);
+            let localized = AppError::UnprocessableEntity(body).error_response();
+            Ok(res.into_response(localized).map_into_right_body())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::validator::ValidationErrors;
+    use actix_web::{http::StatusCode, test, web, App, HttpResponse};
+    use serde_json::{json, Value as JsonValue};
+
+    async fn blank_title() -> Result<HttpResponse, AppError> {
+        let mut errors = ValidationErrors::default();
+        errors.add("title", "can't be blank");
+        errors.into_result()?;
+        Ok(HttpResponse::Ok().finish())
+    }
+
+    #[actix_web::test]
+    async fn translates_validation_errors() {
+        let app = test::init_service(
+            App::new()
+                .wrap(Localize)
+                .route("/", web::post().to(blank_title)),
+        )
+        .await;
+
+        for (accept_language, message) in [
+            (Some("ja"), "を入力してください"),
+            (Some("fr"), "can't be blank"),
+            (None, "can't be blank"),
+        ] {
+            let mut req = test::TestRequest::post().uri("/");
+            if let Some(accept_language) = accept_language {
+                req = req.insert_header((ACCEPT_LANGUAGE, accept_language));
+            }
+            let res = test::call_service(&app, req.to_request()).await;
+
+            assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+            let body: JsonValue = test::read_body_json(res).await;
+            assert_eq!(body, json!({"errors": {"title": [message]}}));
+        }
+    }
+}
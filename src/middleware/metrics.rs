@@ -0,0 +1,265 @@
+use crate::middleware::state::AppState;
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::StatusCode,
+    web::Data,
+    Error,
+};
+use futures::future::{ok, Ready};
+use futures::Future;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+// Requests which don't match any route share this label to keep cardinality bounded.
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+// Prometheus metrics are reference counted, so clones share the same series.
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    requests_total: IntCounterVec,
+    request_errors_total: IntCounterVec,
+    request_duration_seconds: HistogramVec,
+    db_pool_connections: IntGaugeVec,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+        let requests_total = IntCounterVec::new(
+            Opts::new("http_requests_total", "Number of HTTP requests."),
+            &["method", "route", "status"],
+        )
+        .expect("Failed to create http_requests_total");
+        let request_errors_total = IntCounterVec::new(
+            Opts::new(
+                "http_request_errors_total",
+                "Number of HTTP requests answered with a server error.",
+            ),
+            &["method", "route"],
+        )
+        .expect("Failed to create http_request_errors_total");
+        let request_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latencies in seconds.",
+            ),
+            &["method", "route"],
+        )
+        .expect("Failed to create http_request_duration_seconds");
+        let db_pool_connections = IntGaugeVec::new(
+            Opts::new(
+                "db_pool_connections",
+                "Number of database connections by pool and state.",
+            ),
+            &["pool", "state"],
+        )
+        .expect("Failed to create db_pool_connections");
+
+        registry
+            .register(Box::new(requests_total.clone()))
+            .expect("Failed to register http_requests_total");
+        registry
+            .register(Box::new(request_errors_total.clone()))
+            .expect("Failed to register http_request_errors_total");
+        registry
+            .register(Box::new(request_duration_seconds.clone()))
+            .expect("Failed to register http_request_duration_seconds");
+        registry
+            .register(Box::new(db_pool_connections.clone()))
+            .expect("Failed to register db_pool_connections");
+
+        Self {
+            registry,
+            requests_total,
+            request_errors_total,
+            request_duration_seconds,
+            db_pool_connections,
+        }
+    }
+
+    // A gauge rather than a counter, so it is refreshed right before each scrape.
+    pub fn observe_pool(&self, pool: &str, connections: u32, idle_connections: u32) {
+        let in_use = connections.saturating_sub(idle_connections);
+        self.db_pool_connections
+            .with_label_values(&[pool, "idle"])
+            .set(i64::from(idle_connections));
+        self.db_pool_connections
+            .with_label_values(&[pool, "in_use"])
+            .set(i64::from(in_use));
+    }
+
+    pub fn observe(&self, method: &str, route: &str, status: StatusCode, elapsed: Duration) {
+        self.requests_total
+            .with_label_values(&[method, route, status.as_str()])
+            .inc();
+        if status.is_server_error() {
+            self.request_errors_total
+                .with_label_values(&[method, route])
+                .inc();
+        }
+        self.request_duration_seconds
+            .with_label_values(&[method, route])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub fn render(&self) -> Result<String, prometheus::Error> {
+        let mut buffer = vec![];
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Records every request against `AppState::metrics` when `METRICS_ENABLED` is set.
+// Routes are labeled by their template (e.g. `/api/articles/{article_title_slug}`), never by the raw path.
+pub struct RequestMetrics;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestMetrics
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestMetricsMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestMetricsMiddleware { service })
+    }
+}
+
+pub struct RequestMetricsMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestMetricsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let metrics = req
+            .app_data::<Data<AppState>>()
+            .filter(|state| state.config.metrics_enabled)
+            .map(|state| state.metrics.clone());
+        let method = req.method().to_string();
+        let route = req
+            .match_pattern()
+            .unwrap_or_else(|| UNMATCHED_ROUTE.to_owned());
+        let started_at = Instant::now();
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let res = fut.await?;
+            if let Some(metrics) = metrics {
+                metrics.observe(&method, &route, res.status(), started_at.elapsed());
+            }
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::middleware::catch_panic::CatchPanic;
+    use crate::middleware::rate_limit::RateLimiter;
+    use actix_web::{test, web, App, HttpResponse};
+    use diesel::r2d2::{ConnectionManager, Pool};
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+
+    async fn panics() -> HttpResponse {
+        panic!("boom")
+    }
+
+    #[test]
+    fn render_exposes_observed_requests() {
+        let metrics = Metrics::new();
+        metrics.observe(
+            "GET",
+            "/api/articles/{article_title_slug}",
+            StatusCode::OK,
+            Duration::from_millis(5),
+        );
+        metrics.observe(
+            "GET",
+            "/api/articles/{article_title_slug}",
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Duration::from_millis(5),
+        );
+
+        let text = metrics.render().unwrap();
+        assert!(text.contains(
+            r#"http_requests_total{method="GET",route="/api/articles/{article_title_slug}",status="200"} 1"#
+        ));
+        assert!(text.contains(
+            r#"http_request_errors_total{method="GET",route="/api/articles/{article_title_slug}"} 1"#
+        ));
+        assert!(text.contains("http_request_duration_seconds_bucket"));
+    }
+
+    #[test]
+    fn render_exposes_pool_connections() {
+        let metrics = Metrics::new();
+        metrics.observe_pool("primary", 10, 7);
+
+        let text = metrics.render().unwrap();
+        assert!(text.contains(r#"db_pool_connections{pool="primary",state="idle"} 7"#));
+        assert!(text.contains(r#"db_pool_connections{pool="primary",state="in_use"} 3"#));
+    }
+
+    #[actix_web::test]
+    async fn panic_caught_inside_is_counted_as_error() {
+        let pool = Pool::builder().build_unchecked(ConnectionManager::new(
+            "postgres://unused@127.0.0.1:1/unused",
+        ));
+        let state = AppState {
+            pool,
+            replica_pool: None,
+            config: Config {
+                metrics_enabled: true,
+                ..Config::default()
+            },
+            metrics: Metrics::new(),
+            rate_limiter: RateLimiter::new(),
+            read_only: Arc::new(AtomicBool::new(false)),
+        };
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(state.clone()))
+                .wrap(CatchPanic)
+                .wrap(RequestMetrics)
+                .route("/panic", web::get().to(panics)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/panic").to_request();
+        let res = test::call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let text = state.metrics.render().unwrap();
+        assert!(text.contains(r#"http_request_errors_total{method="GET",route="/panic"} 1"#));
+    }
+}
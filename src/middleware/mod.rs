@@ -1,4 +1,12 @@
 pub mod auth;
+pub mod catch_panic;
+pub mod compress;
 pub mod cors;
 pub mod error;
+pub mod json;
+pub mod localize;
+pub mod metrics;
+pub mod rate_limit;
+pub mod read_only;
+pub mod request_id;
 pub mod state;
@@ -0,0 +1,169 @@
+use crate::app::user::model::User;
+use crate::middleware::error::ErrorResponse;
+use crate::middleware::state::AppState;
+use actix_web::HttpMessage;
+use actix_web::{
+    body::EitherBody,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::RETRY_AFTER,
+    web::Data,
+    Error, HttpResponse,
+};
+use futures::future::{ok, Ready};
+use futures::Future;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+// Past this many users, buckets which have refilled completely are dropped.
+const MAX_IDLE_BUCKETS: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+// In-memory token buckets, one per user, refilling `per_minute` tokens a minute. Clones share the
+// same buckets, but every server process keeps its own.
+#[derive(Clone, Default)]
+pub struct RateLimiter {
+    buckets: Arc<Mutex<HashMap<Uuid, Bucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Takes a token from the user's bucket, or returns how long until there is one again.
+    pub fn check(&self, user_id: Uuid, per_minute: u32, now: Instant) -> Result<(), Duration> {
+        let capacity = f64::from(per_minute);
+        let per_second = capacity / 60.0;
+        let refill = |bucket: &Bucket| {
+            let elapsed = now.saturating_duration_since(bucket.updated_at);
+            (bucket.tokens + elapsed.as_secs_f64() * per_second).min(capacity)
+        };
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|err| err.into_inner());
+        if buckets.len() > MAX_IDLE_BUCKETS {
+            buckets.retain(|_, bucket| refill(bucket) < capacity);
+        }
+        let bucket = buckets.entry(user_id).or_insert(Bucket {
+            tokens: capacity,
+            updated_at: now,
+        });
+        bucket.tokens = refill(bucket);
+        bucket.updated_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+        }
+    }
+}
+
+// Limits writes per authenticated user to `USER_WRITES_PER_MINUTE`, answering 429 with
+// `Retry-After` beyond it. Wrapped around single routes, so it runs after `Authentication`.
+pub struct UserRateLimit;
+
+impl<S, B> Transform<S, ServiceRequest> for UserRateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = UserRateLimitMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(UserRateLimitMiddleware { service })
+    }
+}
+
+pub struct UserRateLimitMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for UserRateLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let checked = match (
+            req.app_data::<Data<AppState>>(),
+            req.extensions().get::<User>(),
+        ) {
+            (Some(state), Some(user)) if state.config.user_writes_per_minute > 0 => state
+                .rate_limiter
+                .check(user.id, state.config.user_writes_per_minute, Instant::now()),
+            _ => Ok(()),
+        };
+
+        match checked {
+            Ok(()) => {
+                let fut = self.service.call(req);
+                Box::pin(async move {
+                    let res = fut.await?.map_into_left_body();
+                    Ok(res)
+                })
+            }
+            Err(retry_after) => Box::pin(async move {
+                let (req, _res) = req.into_parts();
+                // Rounded up, so retrying after that many seconds always succeeds.
+                let retry_after_secs =
+                    retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+                let res = HttpResponse::TooManyRequests()
+                    .insert_header((RETRY_AFTER, retry_after_secs.to_string()))
+                    .json(ErrorResponse::from("too many requests"))
+                    .map_into_right_body();
+                Ok(ServiceResponse::new(req, res))
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_allows_up_to_limit_then_refills() {
+        let limiter = RateLimiter::new();
+        let user_id = Uuid::new_v4();
+        let now = Instant::now();
+
+        for _ in 0..60 {
+            assert!(limiter.check(user_id, 60, now).is_ok());
+        }
+        let retry_after = limiter.check(user_id, 60, now).unwrap_err();
+        assert_eq!(retry_after, Duration::from_secs(1));
+
+        let later = now + Duration::from_secs(1);
+        assert!(limiter.check(user_id, 60, later).is_ok());
+        assert!(limiter.check(user_id, 60, later).is_err());
+    }
+
+    #[test]
+    fn check_keeps_users_apart() {
+        let limiter = RateLimiter::new();
+        let now = Instant::now();
+
+        assert!(limiter.check(Uuid::new_v4(), 1, now).is_ok());
+        assert!(limiter.check(Uuid::new_v4(), 1, now).is_ok());
+    }
+}
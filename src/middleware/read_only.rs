@@ -0,0 +1,94 @@
+use crate::middleware::error::ErrorResponse;
+use crate::middleware::state::AppState;
+use actix_web::{
+    body::EitherBody,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::Method,
+    web::Data,
+    Error, HttpResponse,
+};
+use futures::future::{ok, Ready};
+use futures::Future;
+use std::pin::Pin;
+
+// POST routes which don't write anything, plus the toggle itself so it can be switched back.
+const ALLOWED_WRITE_ROUTES: [&str; 5] = [
+    "/api/users/login",
+    "/api/user/refresh",
+    "/api/articles/batch",
+    "/api/articles/validate",
+    "/api/admin/read-only",
+];
+
+// Answers every mutating request with 503 while `AppState::read_only` is set, e.g. during
+// database maintenance. Wrapped outside `Authentication`, so it doesn't need a database either.
+pub struct ReadOnly;
+
+impl<S, B> Transform<S, ServiceRequest> for ReadOnly
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ReadOnlyMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(ReadOnlyMiddleware { service })
+    }
+}
+
+pub struct ReadOnlyMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for ReadOnlyMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let read_only = req
+            .app_data::<Data<AppState>>()
+            .map(|state| state.is_read_only())
+            .unwrap_or(false);
+
+        if read_only && is_write(&req) {
+            return Box::pin(async move {
+                let (req, _res) = req.into_parts();
+                let res = HttpResponse::ServiceUnavailable()
+                    .json(ErrorResponse::from("service is in read-only mode"))
+                    .map_into_right_body();
+                Ok(ServiceResponse::new(req, res))
+            });
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let res = fut.await?.map_into_left_body();
+            Ok(res)
+        })
+    }
+}
+
+fn is_write(req: &ServiceRequest) -> bool {
+    let method = req.method();
+    if [Method::GET, Method::HEAD, Method::OPTIONS].contains(method) {
+        return false;
+    }
+    match req.match_pattern() {
+        Some(pattern) => !ALLOWED_WRITE_ROUTES.contains(&pattern.as_str()),
+        None => false,
+    }
+}
@@ -0,0 +1,162 @@
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderName, HeaderValue},
+    Error, HttpMessage, HttpRequest,
+};
+use futures::future::{ok, Ready};
+use futures::Future;
+use std::pin::Pin;
+use uuid::Uuid;
+
+// Longer incoming ids are replaced rather than echoed back.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+// `Logger` format with the request id appended. The id is written back to the request headers, so
+// `%{X-Request-Id}i` always has a value as long as `Logger` is wrapped inside `RequestId`.
+pub const LOG_FORMAT: &str =
+    r#"%a "%r" %s %b "%{Referer}i" "%{User-Agent}i" %T request_id=%{X-Request-Id}i"#;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestIdValue(pub String);
+
+pub fn get_request_id(req: &HttpRequest) -> Option<String> {
+    req.extensions()
+        .get::<RequestIdValue>()
+        .map(|request_id| request_id.0.clone())
+}
+
+// Takes `X-Request-Id` from the request or generates a v4 UUID, stores it in the request
+// extensions and echoes it on the response, including error responses.
+pub struct RequestId;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestId
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestIdMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestIdMiddleware { service })
+    }
+}
+
+pub struct RequestIdMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestIdMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        // `from_static` takes the lowercase form of `header::X_REQUEST_ID`.
+        let name = HeaderName::from_static("x-request-id");
+        let value = req
+            .headers()
+            .get(&name)
+            .filter(|value| is_valid(value))
+            .cloned()
+            .unwrap_or_else(|| {
+                HeaderValue::from_str(&Uuid::new_v4().to_string())
+                    .expect("uuid is a valid header value")
+            });
+        let request_id = value.to_str().unwrap_or_default().to_owned();
+        req.headers_mut().insert(name.clone(), value.clone());
+        req.extensions_mut().insert(RequestIdValue(request_id));
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let mut res = fut.await?;
+            res.headers_mut().insert(name, value);
+            Ok(res)
+        })
+    }
+}
+
+fn is_valid(value: &HeaderValue) -> bool {
+    match value.to_str() {
+        Ok(text) => !text.is_empty() && text.len() <= MAX_REQUEST_ID_LENGTH,
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::header;
+    use crate::error::AppError;
+    use actix_web::{http::StatusCode, test, web, App, HttpResponse};
+    use serde_json::json;
+
+    async fn echo(req: HttpRequest) -> HttpResponse {
+        HttpResponse::Ok().body(get_request_id(&req).unwrap_or_default())
+    }
+
+    async fn fail() -> Result<HttpResponse, AppError> {
+        Err(AppError::NotFound(json!({"error": "not found"})))
+    }
+
+    #[actix_web::test]
+    async fn generates_request_id() {
+        let app =
+            test::init_service(App::new().wrap(RequestId).route("/", web::get().to(echo))).await;
+        let req = test::TestRequest::get().uri("/").to_request();
+
+        let res = test::call_service(&app, req).await;
+
+        let header = res.headers().get(header::X_REQUEST_ID).unwrap().clone();
+        assert!(Uuid::parse_str(header.to_str().unwrap()).is_ok());
+        assert_eq!(test::read_body(res).await, header.as_bytes());
+    }
+
+    #[actix_web::test]
+    async fn keeps_incoming_request_id() {
+        let app =
+            test::init_service(App::new().wrap(RequestId).route("/", web::get().to(echo))).await;
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header((header::X_REQUEST_ID, "client-id-1"))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+
+        assert_eq!(
+            res.headers().get(header::X_REQUEST_ID).unwrap(),
+            "client-id-1"
+        );
+        assert_eq!(test::read_body(res).await, "client-id-1");
+    }
+
+    #[actix_web::test]
+    async fn error_response_has_request_id() {
+        let app =
+            test::init_service(App::new().wrap(RequestId).route("/", web::get().to(fail))).await;
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header((header::X_REQUEST_ID, "client-id-2"))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            res.headers().get(header::X_REQUEST_ID).unwrap(),
+            "client-id-2"
+        );
+    }
+}
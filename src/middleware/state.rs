@@ -1,18 +1,103 @@
+use crate::config::Config;
 use crate::error::AppError;
+use crate::middleware::metrics::Metrics;
+use crate::middleware::rate_limit::RateLimiter;
+use crate::middleware::request_id;
 use crate::utils;
+use actix_web::HttpRequest;
 use diesel::pg::PgConnection;
 use diesel::r2d2::{ConnectionManager, PooledConnection};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 type AppConn = PooledConnection<ConnectionManager<PgConnection>>;
 
 #[derive(Clone)]
 pub struct AppState {
     pub pool: utils::db::DbPool,
+    // `None` unless `REPLICA_DATABASE_URL` is set, in which case reads go to the replica.
+    pub replica_pool: Option<utils::db::DbPool>,
+    pub config: Config,
+    pub metrics: Metrics,
+    pub rate_limiter: RateLimiter,
+    // Starts as `READ_ONLY` and can be flipped at runtime, see `middleware::read_only`.
+    pub read_only: Arc<AtomicBool>,
 }
 
 impl AppState {
-    pub fn get_conn(&self) -> Result<AppConn, AppError> {
+    // For handlers which only read. A replica may lag behind, so anything that reads its own
+    // writes (or authenticates a just-created user) should use `write_conn` instead.
+    pub fn read_conn(&self) -> Result<AppConn, AppError> {
+        let pool = self.replica_pool.as_ref().unwrap_or(&self.pool);
+        let conn = pool.get()?;
+        Ok(conn)
+    }
+
+    pub fn write_conn(&self) -> Result<AppConn, AppError> {
         let conn = self.pool.get()?;
         Ok(conn)
     }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::SeqCst)
+    }
+
+    // Runs a database operation, logging it with the request id when it exceeds
+    // `SLOW_QUERY_THRESHOLD_MS`. `operation` names what runs (e.g. "article.list"), not the SQL.
+    pub fn timed<T>(&self, req: &HttpRequest, operation: &str, f: impl FnOnce() -> T) -> T {
+        let request_id = request_id::get_request_id(req);
+        let threshold = Duration::from_millis(self.config.slow_query_threshold_ms);
+        utils::db::timed(operation, request_id.as_deref(), threshold, f)
+    }
+
+    // Updates the pool gauges in `metrics`, e.g. right before rendering them.
+    pub fn observe_pools(&self) {
+        let state = self.pool.state();
+        self.metrics
+            .observe_pool("primary", state.connections, state.idle_connections);
+        if let Some(replica_pool) = &self.replica_pool {
+            let state = replica_pool.state();
+            self.metrics
+                .observe_pool("replica", state.connections, state.idle_connections);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::env_key;
+    use diesel::r2d2::Pool;
+    use std::env;
+    use std::time::Duration;
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn writes_are_not_routed_to_replica() {
+        dotenv::dotenv().ok();
+        let database_url =
+            env::var(env_key::TEST_DATABASE_URL).expect("TEST_DATABASE_URL must be set");
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(ConnectionManager::new(database_url))
+            .unwrap();
+        // Nothing listens on port 1, so any connection taken from the replica fails.
+        let replica_pool = Pool::builder()
+            .connection_timeout(Duration::from_millis(200))
+            .build_unchecked(ConnectionManager::new(
+                "postgres://replica@127.0.0.1:1/replica",
+            ));
+        let state = AppState {
+            pool,
+            replica_pool: Some(replica_pool),
+            config: Config::default(),
+            metrics: Metrics::new(),
+            rate_limiter: RateLimiter::new(),
+            read_only: Arc::new(AtomicBool::new(false)),
+        };
+
+        assert!(state.write_conn().is_ok());
+        assert!(state.read_conn().is_err());
+    }
 }
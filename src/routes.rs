@@ -1,12 +1,22 @@
 use crate::app;
+use crate::error::AppError;
+use crate::middleware::error::ErrorResponse;
+use crate::middleware::rate_limit::UserRateLimit;
+use crate::utils::api::ApiResponse;
 use actix_web::web;
 use actix_web::web::{delete, get, post, put};
+use serde_json::json;
 
 pub fn api(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/api")
             .service(web::scope("/healthcheck").route("", get().to(app::healthcheck::api::index)))
-            .service(web::scope("/tags").route("", get().to(app::tag::api::index)))
+            .service(web::scope("/admin").route("/read-only", put().to(app::admin::api::read_only)))
+            .service(
+                web::scope("/tags")
+                    .route("", get().to(app::tag::api::index))
+                    .route("/popular", get().to(app::tag::api::popular)),
+            )
             .service(
                 web::scope("/users")
                     .route("/login", post().to(app::user::api::signin))
@@ -15,27 +25,59 @@ pub fn api(cfg: &mut web::ServiceConfig) {
             .service(
                 web::scope("/user")
                     .route("", get().to(app::user::api::me))
-                    .route("", put().to(app::user::api::update)),
+                    .route("/refresh", post().to(app::user::api::refresh))
+                    .route("", put().to(app::user::api::update))
+                    .route("", delete().to(app::user::api::delete))
+                    .route("/image", post().to(app::user::api::upload_image)),
             )
             .service(
                 web::scope("/profiles")
+                    .route("/follow", post().to(app::profile::api::follow_many))
                     .route("/{username}", get().to(app::profile::api::show))
+                    .route(
+                        "/{username}/articles",
+                        get().to(app::article::api::index_by_author),
+                    )
+                    .route(
+                        "/{username}/followers",
+                        get().to(app::profile::api::followers),
+                    )
+                    .route(
+                        "/{username}/following",
+                        get().to(app::profile::api::following),
+                    )
                     .route("/{username}/follow", post().to(app::profile::api::follow))
                     .route(
                         "/{username}/follow",
                         delete().to(app::profile::api::unfollow),
+                    )
+                    .route(
+                        "/{username}/follow-status",
+                        get().to(app::profile::api::follow_status),
                     ),
             )
             .service(
                 web::scope("/articles")
                     .route("/feed", get().to(app::article::api::feed))
+                    .route("/batch", post().to(app::article::api::batch))
+                    .route(
+                        "/bulk-delete",
+                        post()
+                            .to(app::article::api::bulk_delete)
+                            .wrap(UserRateLimit),
+                    )
+                    .route("/validate", post().to(app::article::api::validate))
+                    .route("/tag/{tag_name}", get().to(app::article::api::index_by_tag))
                     .route("", get().to(app::article::api::index))
-                    .route("", post().to(app::article::api::create))
+                    .route("", post().to(app::article::api::create).wrap(UserRateLimit))
                     .service(
                         web::scope("/{article_title_slug}")
                             .route("", get().to(app::article::api::show))
-                            .route("", put().to(app::article::api::update))
-                            .route("", delete().to(app::article::api::delete))
+                            .route("", put().to(app::article::api::update).wrap(UserRateLimit))
+                            .route(
+                                "",
+                                delete().to(app::article::api::delete).wrap(UserRateLimit),
+                            )
                             .service(
                                 web::scope("/favorite")
                                     .route("", post().to(app::favorite::api::favorite))
@@ -44,10 +86,43 @@ pub fn api(cfg: &mut web::ServiceConfig) {
                             .service(
                                 web::scope("/comments")
                                     .route("", get().to(app::comment::api::index))
-                                    .route("", post().to(app::comment::api::create))
-                                    .route("/{comment_id}", delete().to(app::comment::api::delete)),
+                                    .route(
+                                        "",
+                                        post().to(app::comment::api::create).wrap(UserRateLimit),
+                                    )
+                                    .route(
+                                        "/{comment_id}",
+                                        delete().to(app::comment::api::delete).wrap(UserRateLimit),
+                                    ),
                             ),
                     ),
             ),
     );
+    cfg.service(web::scope("/api-docs").route("/openapi.json", get().to(app::openapi::api::index)));
+    cfg.service(web::scope("/metrics").route("", get().to(app::metrics::api::index)));
+    cfg.default_service(web::to(not_found));
+}
+
+// Unmatched paths get the same JSON envelope as every other error.
+async fn not_found() -> ApiResponse {
+    Err(AppError::NotFound(json!(ErrorResponse::from("not found"))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test, App};
+    use serde_json::Value as JsonValue;
+
+    #[actix_web::test]
+    async fn unknown_route_is_json_not_found() {
+        let app = test::init_service(App::new().configure(api)).await;
+        let req = test::TestRequest::get().uri("/no/such/route").to_request();
+
+        let res = test::call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        let body: JsonValue = test::read_body_json(res).await;
+        assert_eq!(body, json!({"errors": {"body": ["not found"]}}));
+    }
 }
@@ -8,8 +8,10 @@ diesel::table! {
         title -> Text,
         description -> Text,
         body -> Text,
-        created_at -> Timestamp,
-        updated_at -> Timestamp,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        deleted_at -> Nullable<Timestamptz>,
+        view_count -> Int8,
     }
 }
 
@@ -19,18 +21,17 @@ diesel::table! {
         article_id -> Uuid,
         author_id -> Uuid,
         body -> Text,
-        create_at -> Timestamp,
-        updated_at -> Timestamp,
+        create_at -> Timestamptz,
+        updated_at -> Timestamptz,
     }
 }
 
 diesel::table! {
-    favorites (id) {
-        id -> Uuid,
+    favorites (user_id, article_id) {
         article_id -> Uuid,
         user_id -> Uuid,
-        created_at -> Timestamp,
-        updated_at -> Timestamp,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
     }
 }
 
@@ -38,8 +39,18 @@ diesel::table! {
     follows (follower_id, followee_id) {
         followee_id -> Uuid,
         follower_id -> Uuid,
-        created_at -> Timestamp,
-        updated_at -> Timestamp,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    idempotency_keys (user_id, key) {
+        user_id -> Uuid,
+        key -> Text,
+        response -> Nullable<Jsonb>,
+        created_at -> Timestamptz,
+        request_hash -> Nullable<Text>,
     }
 }
 
@@ -48,8 +59,8 @@ diesel::table! {
         id -> Uuid,
         article_id -> Uuid,
         name -> Text,
-        created_at -> Timestamp,
-        updated_at -> Timestamp,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
     }
 }
 
@@ -61,8 +72,9 @@ diesel::table! {
         password -> Text,
         bio -> Nullable<Text>,
         image -> Nullable<Text>,
-        created_at -> Timestamp,
-        updated_at -> Timestamp,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        last_login_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -71,6 +83,7 @@ diesel::joinable!(comments -> articles (article_id));
 diesel::joinable!(comments -> users (author_id));
 diesel::joinable!(favorites -> articles (article_id));
 diesel::joinable!(favorites -> users (user_id));
+diesel::joinable!(idempotency_keys -> users (user_id));
 diesel::joinable!(tags -> articles (article_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
@@ -78,6 +91,7 @@ diesel::allow_tables_to_appear_in_same_query!(
     comments,
     favorites,
     follows,
+    idempotency_keys,
     tags,
     users,
 );
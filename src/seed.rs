@@ -0,0 +1,125 @@
+use crate::app::article::model::Article;
+use crate::app::article::service::{self, CreateArticleSerivce};
+use crate::app::comment::model::{Comment, CreateComment};
+use crate::app::favorite::model::{CreateFavorite, Favorite};
+use crate::app::user::model::User;
+use crate::config::Config;
+use crate::error::AppError;
+use diesel::pg::PgConnection;
+
+const PASSWORD: &str = "password";
+
+// (username, bio)
+const USERS: [(&str, &str); 3] = [
+    ("jake", "I work at statefarm"),
+    ("alice", "Rustacean"),
+    ("bob", "Writes about databases"),
+];
+
+// (follower, followee)
+const FOLLOWS: [(&str, &str); 3] = [("jake", "alice"), ("alice", "bob"), ("bob", "jake")];
+
+// (author, title, tags, commenter, favoriter)
+const ARTICLES: [(&str, &str, &[&str], &str, &str); 3] = [
+    (
+        "jake",
+        "How to train your dragon",
+        &["dragons", "training"],
+        "alice",
+        "bob",
+    ),
+    (
+        "alice",
+        "Getting started with actix-web",
+        &["rust", "actix"],
+        "bob",
+        "jake",
+    ),
+    (
+        "bob",
+        "Diesel in practice",
+        &["rust", "diesel"],
+        "jake",
+        "alice",
+    ),
+];
+
+// Populates the database with demo data through the regular model functions.
+// Rows which already exist are left as they are, so it's safe to run more than once.
+pub fn run(conn: &PgConnection, config: &Config) -> Result<(), AppError> {
+    for (username, bio) in USERS {
+        find_or_signup(conn, config, username, bio)?;
+    }
+
+    for (follower, followee) in FOLLOWS {
+        let follower = User::find_by_username(conn, follower)?;
+        let followee = User::find_by_username(conn, followee)?;
+        if !follower.is_following(conn, &followee.id) {
+            follower.follow(conn, &followee.username)?;
+        }
+    }
+
+    for (author, title, tags, commenter, favoriter) in ARTICLES {
+        let slug = Article::convert_title_to_slug(title, config.slug_max_length);
+        if Article::fetch_by_slug_with_author(conn, &slug).is_ok() {
+            info!("Skip existing article {}", slug);
+            continue;
+        }
+        let author = User::find_by_username(conn, author)?;
+        let (article, _, _, _, _) = service::create(
+            conn,
+            &CreateArticleSerivce {
+                slug,
+                title: title.to_owned(),
+                description: format!("About {}", title.to_lowercase()),
+                body: format!("This is an article about {}.", title.to_lowercase()),
+                tag_name_list: Some(tags.iter().map(|tag| tag.to_string()).collect()),
+                author_id: author.id,
+            },
+        )?;
+
+        let commenter = User::find_by_username(conn, commenter)?;
+        Comment::create(
+            conn,
+            &CreateComment {
+                body: "Great article!".to_owned(),
+                author_id: commenter.id,
+                article_id: article.id,
+            },
+        )?;
+
+        let favoriter = User::find_by_username(conn, favoriter)?;
+        Favorite::create(
+            conn,
+            &CreateFavorite {
+                user_id: favoriter.id,
+                article_id: article.id,
+            },
+        )?;
+    }
+
+    Ok(())
+}
+
+fn find_or_signup(
+    conn: &PgConnection,
+    config: &Config,
+    username: &str,
+    bio: &str,
+) -> Result<User, AppError> {
+    if let Ok(user) = User::find_by_username(conn, username) {
+        info!("Skip existing user {}", username);
+        return Ok(user);
+    }
+    let email = format!("{}@example.com", username);
+    let (user, _token) = User::signup(
+        conn,
+        &email,
+        username,
+        PASSWORD,
+        config.bcrypt_cost,
+        &config.token,
+    )?;
+    info!("Created user {} ({} / {})", username, email, PASSWORD);
+    Ok(user)
+}
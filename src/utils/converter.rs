@@ -1,7 +1,40 @@
-use convert_case::{Case, Casing};
+use deunicode::deunicode_char;
 
-pub fn to_kebab(text: &str) -> String {
-    text.to_case(Case::Kebab)
+// Transliterates letters and digits to ASCII and joins the words with single hyphens.
+// Anything else (punctuation, emoji) only separates words, so the result may be empty.
+pub fn to_slug(text: &str) -> String {
+    let ascii = text
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                deunicode_char(c).unwrap_or("")
+            } else {
+                " "
+            }
+        })
+        .collect::<String>()
+        .to_lowercase();
+    ascii
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+// Shortens a slug from `to_slug` to at most `max_length`, dropping whole words where possible.
+// Slugs are ASCII by then, so cutting at any byte is a char boundary.
+pub fn truncate_slug(slug: &str, max_length: usize) -> &str {
+    if slug.len() <= max_length {
+        return slug;
+    }
+    if slug.as_bytes()[max_length] == b'-' {
+        return &slug[..max_length];
+    }
+    match slug[..max_length].rfind('-') {
+        Some(end) => &slug[..end],
+        // A single word longer than the limit is cut wherever it hits the limit.
+        None => &slug[..max_length],
+    }
 }
 
 #[cfg(test)]
@@ -9,7 +42,47 @@ mod tests {
     use super::*;
 
     #[test]
-    fn str_to_kebab() {
-        assert_eq!("this-is-blog-title", to_kebab("this is blog title"));
+    fn str_to_slug() {
+        assert_eq!("this-is-blog-title", to_slug("this is blog title"));
+        assert_eq!("this-is-blog-title", to_slug("  This -- is Blog title! "));
+    }
+
+    #[test]
+    fn accented_str_to_slug() {
+        assert_eq!("creme-brulee-a-la-mode", to_slug("Crème brûlée à la mode"));
+    }
+
+    #[test]
+    fn truncate_slug_at_word_boundary() {
+        let title = "word ".repeat(60);
+        assert_eq!(title.len(), 300);
+        let slug = to_slug(&title);
+
+        let truncated = truncate_slug(&slug, 80);
+        assert_eq!(truncated.len(), 79);
+        assert!(truncated.starts_with("word-word"));
+        assert!(truncated.ends_with("-word"));
+
+        // The limit falls right before a hyphen, so nothing more has to go.
+        assert_eq!(truncate_slug(&slug, 79), truncated);
+        assert_eq!(truncate_slug("short-slug", 80), "short-slug");
+    }
+
+    #[test]
+    fn truncate_slug_of_transliterated_title() {
+        let title = "é".repeat(300);
+        let slug = to_slug(&title);
+        assert_eq!(truncate_slug(&slug, 80), "e".repeat(80));
+
+        let slug = to_slug(&"crème brûlée ".repeat(25));
+        let truncated = truncate_slug(&slug, 80);
+        assert!(truncated.len() <= 80);
+        assert!(truncated.ends_with("-creme") || truncated.ends_with("-brulee"));
+    }
+
+    #[test]
+    fn punctuation_and_emoji_to_empty_slug() {
+        assert_eq!("", to_slug("!!! ??? ..."));
+        assert_eq!("", to_slug("🎉🎉🎉"));
     }
 }
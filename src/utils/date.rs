@@ -1,7 +1,9 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize, Serializer};
 
+// Timestamps are stored as UTC, so they are rendered as RFC3339 with a `Z` suffix.
 #[derive(Debug, PartialEq, Deserialize)]
-pub struct Iso8601(pub chrono::NaiveDateTime);
+pub struct Iso8601(pub DateTime<Utc>);
 
 impl Serialize for Iso8601 {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
@@ -9,3 +11,26 @@ impl Serialize for Iso8601 {
         serializer.serialize_str(&s.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn serializes_as_utc_rfc3339() {
+        let date = Iso8601(Utc.ymd(2021, 1, 1).and_hms_milli(0, 0, 0, 0));
+        let json = serde_json::to_string(&date).unwrap();
+        assert_eq!(json, r#""2021-01-01T00:00:00.000Z""#);
+    }
+
+    #[test]
+    fn serialized_value_parses_as_rfc3339() {
+        let date = Iso8601(Utc.ymd(2021, 10, 24).and_hms_milli(23, 7, 44, 123));
+        let json = serde_json::to_value(&date).unwrap();
+        let s = json.as_str().unwrap();
+        assert!(s.ends_with('Z'));
+        let parsed = DateTime::parse_from_rfc3339(s).unwrap();
+        assert_eq!(parsed.with_timezone(&Utc), date.0);
+    }
+}
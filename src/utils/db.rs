@@ -1,8 +1,12 @@
 use crate::constants::env_key;
+use crate::error::AppError;
+use diesel::connection::TransactionManager;
 use diesel::pg::PgConnection;
 use diesel::r2d2::{ConnectionManager, Pool, PoolError};
+use diesel::Connection;
 use dotenv::dotenv;
 use std::env;
+use std::time::{Duration, Instant};
 
 pub type DbPool = Pool<ConnectionManager<PgConnection>>;
 
@@ -16,3 +20,238 @@ pub fn establish_connection() -> DbPool {
     let database_url = env::var(env_key::DATABASE_URL).expect("DATABASE_URL must be set");
     init_pool(&database_url).expect("Failed to create pool")
 }
+
+pub fn establish_replica_connection() -> Option<DbPool> {
+    dotenv().ok();
+    let database_url = env::var(env_key::REPLICA_DATABASE_URL).ok()?;
+    Some(init_pool(&database_url).expect("Failed to create replica pool"))
+}
+
+// Runs `f` in a transaction, running it again up to `attempts` times in total when it is aborted
+// by a serialization failure or deadlock (`AppError::Conflict`). Any other error is returned as is.
+// NOTE: nested in another transaction, `f` only gets a savepoint, and running it again can't get
+// past a conflict of the outer transaction. So it runs once and the outer one retries, if at all.
+pub fn with_retry<T, F>(conn: &PgConnection, attempts: usize, mut f: F) -> Result<T, AppError>
+where
+    F: FnMut(&PgConnection) -> Result<T, AppError>,
+{
+    let attempts = if conn.transaction_manager().get_transaction_depth() > 0 {
+        1
+    } else {
+        attempts
+    };
+    let mut attempt = 1;
+    loop {
+        match conn.transaction(|| f(conn)) {
+            Err(AppError::Conflict(_)) if attempt < attempts => {
+                info!(
+                    "Retrying transaction aborted by a conflict (attempt {})",
+                    attempt
+                );
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+// Runs `f`, logging a warning when it takes longer than `threshold`.
+pub fn timed<T>(
+    operation: &str,
+    request_id: Option<&str>,
+    threshold: Duration,
+    f: impl FnOnce() -> T,
+) -> T {
+    let started_at = Instant::now();
+    let result = f();
+    let elapsed = started_at.elapsed();
+    if elapsed > threshold {
+        warn!(
+            "Slow query: {} took {}ms (request_id={})",
+            operation,
+            elapsed.as_millis(),
+            request_id.unwrap_or_default()
+        );
+    }
+    result
+}
+
+// NOTE: the transaction is never committed, so everything is rolled back when the connection drops.
+#[cfg(test)]
+pub fn establish_test_connection() -> PgConnection {
+    dotenv().ok();
+    let database_url = env::var(env_key::TEST_DATABASE_URL).expect("TEST_DATABASE_URL must be set");
+    let conn = PgConnection::establish(&database_url).expect("Failed to connect test database");
+    conn.begin_test_transaction()
+        .expect("Failed to begin test transaction");
+    conn
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::result::{DatabaseErrorInformation, DatabaseErrorKind, Error as DieselError};
+    use log::{LevelFilter, Log, Metadata, Record};
+    use std::sync::{Mutex, Once};
+    use std::thread;
+
+    // Keeps every log line, so tests can assert on what was logged.
+    struct CapturingLogger;
+
+    static LOGGER: CapturingLogger = CapturingLogger;
+    static LOGGED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    impl Log for CapturingLogger {
+        fn enabled(&self, _metadata: &Metadata) -> bool {
+            true
+        }
+        fn log(&self, record: &Record) {
+            LOGGED.lock().unwrap().push(record.args().to_string());
+        }
+        fn flush(&self) {}
+    }
+
+    fn logged() -> Vec<String> {
+        LOGGED.lock().unwrap().clone()
+    }
+
+    fn capture_logs() {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| {
+            log::set_logger(&LOGGER).expect("logger already set");
+            log::set_max_level(LevelFilter::Info);
+        });
+    }
+
+    #[test]
+    fn timed_logs_slow_operation_by_name() {
+        capture_logs();
+
+        let result = timed(
+            "article.slow_list",
+            Some("slow-request"),
+            Duration::from_millis(10),
+            || {
+                thread::sleep(Duration::from_millis(20));
+                42
+            },
+        );
+
+        assert_eq!(result, 42);
+        assert!(logged().iter().any(|line| line
+            .starts_with("Slow query: article.slow_list took ")
+            && line.ends_with("ms (request_id=slow-request)")));
+    }
+
+    #[test]
+    fn timed_does_not_log_fast_operation() {
+        capture_logs();
+
+        timed("tag.fast_list", None, Duration::from_secs(60), || ());
+
+        assert!(!logged().iter().any(|line| line.contains("tag.fast_list")));
+    }
+
+    struct SerializationFailure;
+
+    impl DatabaseErrorInformation for SerializationFailure {
+        fn message(&self) -> &str {
+            "could not serialize access due to concurrent update"
+        }
+        fn details(&self) -> Option<&str> {
+            None
+        }
+        fn hint(&self) -> Option<&str> {
+            None
+        }
+        fn table_name(&self) -> Option<&str> {
+            None
+        }
+        fn column_name(&self) -> Option<&str> {
+            None
+        }
+        fn constraint_name(&self) -> Option<&str> {
+            None
+        }
+    }
+
+    // Outside a transaction, unlike `establish_test_connection`, so `with_retry` is the top
+    // level. The closures don't write, so there is nothing to roll back.
+    fn establish_top_level_connection() -> PgConnection {
+        dotenv().ok();
+        let database_url =
+            env::var(env_key::TEST_DATABASE_URL).expect("TEST_DATABASE_URL must be set");
+        PgConnection::establish(&database_url).expect("Failed to connect test database")
+    }
+
+    fn serialization_failure() -> AppError {
+        DieselError::DatabaseError(
+            DatabaseErrorKind::SerializationFailure,
+            Box::new(SerializationFailure),
+        )
+        .into()
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn with_retry_succeeds_on_second_attempt() {
+        let conn = establish_top_level_connection();
+        let mut calls = 0;
+
+        let result = with_retry(&conn, 3, |_| {
+            calls += 1;
+            if calls == 1 {
+                Err(serialization_failure())
+            } else {
+                Ok(calls)
+            }
+        });
+
+        assert_eq!(result.unwrap(), 2);
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn with_retry_gives_up_after_attempts() {
+        let conn = establish_top_level_connection();
+        let mut calls = 0;
+
+        let result: Result<(), _> = with_retry(&conn, 3, |_| {
+            calls += 1;
+            Err(serialization_failure())
+        });
+
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn with_retry_does_not_retry_other_errors() {
+        let conn = establish_top_level_connection();
+        let mut calls = 0;
+
+        let result: Result<(), _> = with_retry(&conn, 3, |_| {
+            calls += 1;
+            Err(DieselError::NotFound.into())
+        });
+
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    fn with_retry_does_not_retry_when_nested() {
+        let conn = establish_test_connection();
+        let mut calls = 0;
+
+        let result: Result<(), _> = with_retry(&conn, 3, |_| {
+            calls += 1;
+            Err(serialization_failure())
+        });
+
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+        assert_eq!(calls, 1);
+    }
+}
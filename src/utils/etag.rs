@@ -0,0 +1,71 @@
+use crate::error::AppError;
+use actix_web::http::header::{EntityTag, IfNoneMatch};
+use actix_web::HttpRequest;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+
+// Hashes the serialized response, so anything visible in it (timestamps, tags, counts) changes the
+// tag. The tag is weak because compression may change the bytes on the wire.
+// NOTE: `DefaultHasher` is only stable within one build, so tags may change across deploys.
+pub fn of<T: Serialize>(value: &T) -> Result<EntityTag, AppError> {
+    let bytes = serde_json::to_vec(value).map_err(|_| AppError::InternalServerError)?;
+    let mut hasher = DefaultHasher::new();
+    hasher.write(&bytes);
+    Ok(EntityTag::new_weak(format!("{:016x}", hasher.finish())))
+}
+
+// Whether the client already holds `etag`, i.e. a 304 can be returned.
+pub fn is_fresh(req: &HttpRequest, etag: &EntityTag) -> bool {
+    match req.get_header::<IfNoneMatch>() {
+        Some(IfNoneMatch::Any) => true,
+        Some(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(etag)),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header;
+    use actix_web::test::TestRequest;
+    use serde_json::json;
+
+    #[test]
+    fn of_changes_with_value() {
+        let etag = of(&json!({"updatedAt": "2022-01-01T00:00:00.000Z"})).unwrap();
+
+        assert!(etag.weak);
+        assert_eq!(
+            etag,
+            of(&json!({"updatedAt": "2022-01-01T00:00:00.000Z"})).unwrap()
+        );
+        assert_ne!(
+            etag,
+            of(&json!({"updatedAt": "2022-01-02T00:00:00.000Z"})).unwrap()
+        );
+    }
+
+    #[test]
+    fn is_fresh_test() {
+        let etag = of(&json!({"favoritesCount": 1})).unwrap();
+
+        let req = TestRequest::default().to_http_request();
+        assert!(!is_fresh(&req, &etag));
+
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, etag.to_string()))
+            .to_http_request();
+        assert!(is_fresh(&req, &etag));
+
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, "W/\"stale\""))
+            .to_http_request();
+        assert!(!is_fresh(&req, &etag));
+
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, "*"))
+            .to_http_request();
+        assert!(is_fresh(&req, &etag));
+    }
+}
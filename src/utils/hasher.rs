@@ -1,6 +1,10 @@
 pub use bcrypt::verify;
-use bcrypt::{hash, BcryptResult, DEFAULT_COST};
+use bcrypt::{hash, BcryptResult};
 
-pub fn hash_password(naive_pw: &str) -> BcryptResult<String> {
-    hash(&naive_pw, DEFAULT_COST)
+// Bcrypt rejects costs outside of this range.
+pub const MIN_COST: u32 = 4;
+pub const MAX_COST: u32 = 31;
+
+pub fn hash_password(naive_pw: &str, cost: u32) -> BcryptResult<String> {
+    hash(&naive_pw, cost)
 }
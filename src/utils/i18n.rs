@@ -0,0 +1,170 @@
+use serde_json::Value as JsonValue;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lang {
+    En,
+    Ja,
+}
+
+impl Lang {
+    // Picks the most preferred supported language from an `Accept-Language` header such as
+    // "ja-JP,ja;q=0.9,en;q=0.8", falling back to English.
+    pub fn from_accept_language(header: Option<&str>) -> Self {
+        let mut ranges = header
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = parts.next()?.trim();
+                let quality = parts
+                    .find_map(|param| param.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+                Some((tag, quality))
+            })
+            .filter(|(_, quality)| *quality > 0.0)
+            .collect::<Vec<_>>();
+        // A stable sort, so equally preferred languages keep the client's order.
+        ranges.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        ranges
+            .into_iter()
+            .find_map(|(tag, _)| Self::from_tag(tag))
+            .unwrap_or(Lang::En)
+    }
+
+    fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split('-').next().unwrap_or_default();
+        if primary.eq_ignore_ascii_case("en") {
+            Some(Lang::En)
+        } else if primary.eq_ignore_ascii_case("ja") {
+            Some(Lang::Ja)
+        } else {
+            None
+        }
+    }
+}
+
+// English validation messages and their Japanese translations. `{}` stands for a number.
+const JA: [(&str, &str); 10] = [
+    ("can't be blank", "を入力してください"),
+    ("is invalid", "は不正な値です"),
+    ("has already been taken", "はすでに使用されています"),
+    (
+        "must not have both email and username",
+        "には email と username の両方を指定できません",
+    ),
+    (
+        "must have either email or username",
+        "には email か username のどちらかを指定してください",
+    ),
+    (
+        "is too short (minimum is {} characters)",
+        "は {} 文字以上で入力してください",
+    ),
+    (
+        "is too long (maximum is {} bytes)",
+        "は {} バイト以内で入力してください",
+    ),
+    (
+        "is too long (maximum is {} tags)",
+        "は {} 個以内で指定してください",
+    ),
+    (
+        "is too long (maximum is {} slugs)",
+        "は {} 件以内で指定してください",
+    ),
+    (
+        "is too long (maximum is {} usernames)",
+        "は {} 件以内で指定してください",
+    ),
+];
+
+// Translates a validation message, keeping it in English when the catalog has no entry.
+pub fn translate(message: &str, lang: Lang) -> String {
+    let catalog = match lang {
+        Lang::En => return message.to_owned(),
+        Lang::Ja => &JA,
+    };
+    catalog
+        .iter()
+        .find_map(|(english, translated)| match english.split_once("{}") {
+            None => (message == *english).then(|| translated.to_string()),
+            Some((prefix, suffix)) => {
+                let number = message.strip_prefix(prefix)?.strip_suffix(suffix)?;
+                (!number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()))
+                    .then(|| translated.replacen("{}", number, 1))
+            }
+        })
+        .unwrap_or_else(|| message.to_owned())
+}
+
+// Translates the messages of a `{"errors":{"<field>":["<message>"]}}` body in place. Field names
+// are left as they are.
+pub fn translate_errors(body: &mut JsonValue, lang: Lang) {
+    let fields = match body.get_mut("errors").and_then(JsonValue::as_object_mut) {
+        Some(fields) => fields,
+        None => return,
+    };
+    for message in fields
+        .values_mut()
+        .filter_map(JsonValue::as_array_mut)
+        .flatten()
+    {
+        if let Some(text) = message.as_str() {
+            *message = JsonValue::String(translate(text, lang));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn accept_language_picks_most_preferred_supported() {
+        assert_eq!(Lang::from_accept_language(Some("ja")), Lang::Ja);
+        assert_eq!(Lang::from_accept_language(Some("ja-JP,en;q=0.8")), Lang::Ja);
+        assert_eq!(
+            Lang::from_accept_language(Some("en;q=0.5,ja;q=0.9")),
+            Lang::Ja
+        );
+        assert_eq!(Lang::from_accept_language(Some("fr,ja;q=0.5")), Lang::Ja);
+        assert_eq!(Lang::from_accept_language(Some("en-US,ja;q=0.5")), Lang::En);
+    }
+
+    #[test]
+    fn accept_language_falls_back_to_english() {
+        assert_eq!(Lang::from_accept_language(None), Lang::En);
+        assert_eq!(Lang::from_accept_language(Some("fr-FR,de")), Lang::En);
+        assert_eq!(Lang::from_accept_language(Some("ja;q=0")), Lang::En);
+        assert_eq!(Lang::from_accept_language(Some("*")), Lang::En);
+    }
+
+    #[test]
+    fn translate_fills_in_numbers() {
+        assert_eq!(translate("can't be blank", Lang::Ja), "を入力してください");
+        assert_eq!(
+            translate("is too short (minimum is 8 characters)", Lang::Ja),
+            "は 8 文字以上で入力してください"
+        );
+        assert_eq!(
+            translate("is too short (minimum is 8 characters)", Lang::En),
+            "is too short (minimum is 8 characters)"
+        );
+    }
+
+    #[test]
+    fn translate_keeps_unknown_messages() {
+        assert_eq!(translate("is unheard of", Lang::Ja), "is unheard of");
+    }
+
+    #[test]
+    fn translate_errors_keeps_field_names() {
+        let mut body = json!({"errors": {"email": ["is invalid", "has already been taken"]}});
+        translate_errors(&mut body, Lang::Ja);
+        assert_eq!(
+            body,
+            json!({"errors": {"email": ["は不正な値です", "はすでに使用されています"]}})
+        );
+    }
+}
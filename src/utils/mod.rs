@@ -2,6 +2,16 @@ pub mod api;
 pub mod converter;
 pub mod date;
 pub mod db;
+pub mod etag;
 pub mod hasher;
+pub mod i18n;
+pub mod nullable;
+pub mod pagination;
+pub mod storage;
+#[cfg(test)]
+pub mod test_support;
+pub mod tls;
 pub mod token;
+pub mod trimmed;
 pub mod uuid;
+pub mod validator;
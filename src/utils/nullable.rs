@@ -0,0 +1,44 @@
+use serde::{Deserialize, Deserializer};
+
+// Tells an omitted field apart from an explicit `null` for nullable columns.
+// Use with `#[serde(default, deserialize_with = "nullable::deserialize")]`:
+// - omitted => `None` (leave the column as is)
+// - `null` => `Some(None)` (set the column to NULL)
+// - value => `Some(Some(value))`
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Deserialize)]
+    struct Item {
+        #[serde(default, deserialize_with = "deserialize")]
+        bio: Option<Option<String>>,
+    }
+
+    #[test]
+    fn omitted_field_is_none() {
+        let item: Item = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(item.bio, None);
+    }
+
+    #[test]
+    fn null_field_is_some_none() {
+        let item: Item = serde_json::from_value(json!({ "bio": null })).unwrap();
+        assert_eq!(item.bio, Some(None));
+    }
+
+    #[test]
+    fn present_field_is_some_some() {
+        let item: Item = serde_json::from_value(json!({ "bio": "hello" })).unwrap();
+        assert_eq!(item.bio, Some(Some("hello".to_owned())));
+    }
+}
@@ -0,0 +1,139 @@
+use crate::config::PaginationConfig;
+use crate::error::AppError;
+use crate::middleware::error::ErrorResponse;
+use crate::middleware::state::AppState;
+use actix_web::dev::Payload;
+use actix_web::{web, FromRequest, HttpRequest};
+use futures::future::{ready, Ready};
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::IntoParams;
+
+// The `limit`/`offset` query parameters of list endpoints, for their OpenAPI docs.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PaginationQuery {
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+// Resolved `limit`/`offset` of list endpoints. Handlers take it as an extractor:
+// a missing limit falls back to the default and a too large one is clamped to the max,
+// while negative or non-numeric values are rejected with 400.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pagination {
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl Pagination {
+    pub fn new(
+        config: &PaginationConfig,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Self, AppError> {
+        let limit = limit.unwrap_or(config.default_limit);
+        let offset = offset.unwrap_or(0);
+        if limit < 0 {
+            return Err(AppError::BadRequest(json!(ErrorResponse::from(
+                "limit must not be negative"
+            ))));
+        }
+        if offset < 0 {
+            return Err(AppError::BadRequest(json!(ErrorResponse::from(
+                "offset must not be negative"
+            ))));
+        }
+        Ok(Self {
+            limit: std::cmp::min(limit, config.max_limit),
+            offset,
+        })
+    }
+
+    pub fn from_query(config: &PaginationConfig, query: &str) -> Result<Self, AppError> {
+        let query = web::Query::<PaginationQuery>::from_query(query).map_err(|_| {
+            AppError::BadRequest(json!(ErrorResponse::from(
+                "limit and offset must be integers"
+            )))
+        })?;
+        Self::new(config, query.limit, query.offset)
+    }
+}
+
+impl FromRequest for Pagination {
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let pagination = req
+            .app_data::<web::Data<AppState>>()
+            .ok_or(AppError::InternalServerError)
+            .and_then(|state| Self::from_query(&state.config.pagination, req.query_string()));
+        ready(pagination)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> PaginationConfig {
+        PaginationConfig {
+            default_limit: 20,
+            max_limit: 100,
+        }
+    }
+
+    #[test]
+    fn defaults_when_missing() {
+        assert_eq!(
+            Pagination::from_query(&config(), "").unwrap(),
+            Pagination {
+                limit: 20,
+                offset: 0
+            }
+        );
+    }
+
+    #[test]
+    fn clamps_limit_to_max() {
+        assert_eq!(
+            Pagination::from_query(&config(), "limit=500&offset=40").unwrap(),
+            Pagination {
+                limit: 100,
+                offset: 40
+            }
+        );
+    }
+
+    #[test]
+    fn ignores_other_parameters() {
+        assert_eq!(
+            Pagination::from_query(&config(), "tag=rust&limit=5").unwrap(),
+            Pagination {
+                limit: 5,
+                offset: 0
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_negative_values() {
+        assert!(matches!(
+            Pagination::from_query(&config(), "limit=-1"),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(matches!(
+            Pagination::from_query(&config(), "offset=-1"),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_non_numeric_values() {
+        assert!(matches!(
+            Pagination::from_query(&config(), "limit=ten"),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+}
@@ -0,0 +1,106 @@
+use crate::config::S3Config;
+use crate::error::AppError;
+use s3::bucket::Bucket;
+use s3::creds::Credentials;
+use s3::region::Region;
+
+// Uploaded images are served as is, so only formats browsers render are accepted.
+const IMAGE_CONTENT_TYPES: [(&str, &str); 4] = [
+    ("image/png", "png"),
+    ("image/jpeg", "jpg"),
+    ("image/gif", "gif"),
+    ("image/webp", "webp"),
+];
+
+// File extension for an accepted image content type, `None` for anything else.
+pub fn image_extension(content_type: &str) -> Option<&'static str> {
+    IMAGE_CONTENT_TYPES
+        .iter()
+        .find(|(accepted, _)| *accepted == content_type)
+        .map(|(_, extension)| *extension)
+}
+
+// Whether `bytes` start with the signature of `content_type`, so an upload can't claim to be an
+// image while being something else.
+pub fn has_image_signature(content_type: &str, bytes: &[u8]) -> bool {
+    match content_type {
+        "image/png" => bytes.starts_with(b"\x89PNG\r\n\x1a\n"),
+        "image/jpeg" => bytes.starts_with(&[0xff, 0xd8, 0xff]),
+        "image/gif" => bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a"),
+        "image/webp" => bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP",
+        _ => false,
+    }
+}
+
+// Stores `bytes` under `key` and returns the public URL of the object.
+pub async fn upload(
+    config: &S3Config,
+    key: &str,
+    bytes: &[u8],
+    content_type: &str,
+) -> Result<String, AppError> {
+    let region = Region::Custom {
+        region: config.region.clone(),
+        endpoint: config.endpoint.clone(),
+    };
+    let credentials = Credentials::new(
+        Some(&config.access_key_id),
+        Some(&config.secret_access_key),
+        None,
+        None,
+        None,
+    )
+    .map_err(|err| {
+        error!("Invalid S3 credentials: {}", err);
+        AppError::InternalServerError
+    })?;
+    // Path-style addressing works with S3-compatible stores like MinIO as well.
+    let bucket =
+        Bucket::new_with_path_style(&config.bucket, region, credentials).map_err(|err| {
+            error!("Invalid S3 bucket: {}", err);
+            AppError::InternalServerError
+        })?;
+    bucket
+        .put_object_with_content_type(key, bytes, content_type)
+        .await
+        .map_err(|err| {
+            error!("Cannot upload {} to S3: {}", key, err);
+            AppError::InternalServerError
+        })?;
+    Ok(format!(
+        "{}/{}",
+        config.public_url.trim_end_matches('/'),
+        key
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_extension_test() {
+        assert_eq!(image_extension("image/png"), Some("png"));
+        assert_eq!(image_extension("image/jpeg"), Some("jpg"));
+        assert_eq!(image_extension("image/svg+xml"), None);
+        assert_eq!(image_extension("text/plain"), None);
+    }
+
+    #[test]
+    fn has_image_signature_test() {
+        assert!(has_image_signature(
+            "image/png",
+            b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"
+        ));
+        assert!(has_image_signature("image/jpeg", &[0xff, 0xd8, 0xff, 0xe0]));
+        assert!(has_image_signature("image/gif", b"GIF89a\x01\0"));
+        assert!(has_image_signature("image/webp", b"RIFF\x24\0\0\0WEBPVP8 "));
+        assert!(!has_image_signature("image/png", b"GIF89a\x01\0"));
+        assert!(!has_image_signature(
+            "image/webp",
+            b"RIFF\x24\0\0\0WAVEfmt "
+        ));
+        assert!(!has_image_signature("image/png", b"<svg onload=alert(1)>"));
+        assert!(!has_image_signature("image/png", b""));
+    }
+}
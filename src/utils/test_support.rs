@@ -0,0 +1,51 @@
+// Fixtures for the tests against the database, see `db::establish_test_connection`.
+use crate::app::article::model::{Article, CreateArticle};
+use crate::app::tag::model::{CreateTag, Tag};
+use crate::app::user::model::User;
+use crate::config::TokenConfig;
+use crate::utils::hasher;
+use diesel::pg::PgConnection;
+use uuid::Uuid;
+
+// Signs up `username` as `<username>@example.com` with the password "password".
+pub fn signup_user(conn: &PgConnection, username: &str) -> User {
+    let (user, _token) = User::signup(
+        conn,
+        &format!("{}@example.com", username),
+        username,
+        "password",
+        hasher::MIN_COST,
+        &TokenConfig::default(),
+    )
+    .unwrap();
+    user
+}
+
+// Creates an article of `author_id` titled after its `slug`, tagged with `tag_names` as given.
+pub fn create_article(
+    conn: &PgConnection,
+    author_id: Uuid,
+    slug: &str,
+    tag_names: &[&str],
+) -> Article {
+    let article = Article::create(
+        conn,
+        &CreateArticle {
+            author_id,
+            slug: slug.to_owned(),
+            title: slug.to_owned(),
+            description: "description".to_owned(),
+            body: "body".to_owned(),
+        },
+    )
+    .unwrap();
+    let records = tag_names
+        .iter()
+        .map(|name| CreateTag {
+            name,
+            article_id: &article.id,
+        })
+        .collect();
+    Tag::create_list(conn, records).unwrap();
+    article
+}
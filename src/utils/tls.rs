@@ -0,0 +1,84 @@
+use crate::config::TlsConfig;
+use rustls::{Certificate, PrivateKey, ServerConfig};
+use rustls_pemfile::Item;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+// Builds the rustls config from the PEM files, so a bad cert or key stops the server on startup.
+pub fn load_server_config(config: &TlsConfig) -> io::Result<ServerConfig> {
+    let certs = load_certs(&config.cert_path)?;
+    let key = load_private_key(&config.key_path)?;
+    ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|err| invalid_data(format!("invalid TLS certificate or key: {}", err)))
+}
+
+fn load_certs(path: &Path) -> io::Result<Vec<Certificate>> {
+    let certs = rustls_pemfile::certs(&mut open(path)?)?;
+    if certs.is_empty() {
+        return Err(invalid_data(format!(
+            "no certificate found in {}",
+            path.display()
+        )));
+    }
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+fn load_private_key(path: &Path) -> io::Result<PrivateKey> {
+    rustls_pemfile::read_all(&mut open(path)?)?
+        .into_iter()
+        .find_map(|item| match item {
+            Item::RSAKey(key) | Item::PKCS8Key(key) | Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or_else(|| invalid_data(format!("no private key found in {}", path.display())))
+}
+
+fn open(path: &Path) -> io::Result<BufReader<File>> {
+    let file = File::open(path).map_err(|err| {
+        io::Error::new(
+            err.kind(),
+            format!("failed to open {}: {}", path.display(), err),
+        )
+    })?;
+    Ok(BufReader::new(file))
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn tls_config(cert_path: &str, key_path: &str) -> TlsConfig {
+        TlsConfig {
+            cert_path: PathBuf::from(cert_path),
+            key_path: PathBuf::from(key_path),
+        }
+    }
+
+    #[test]
+    fn missing_files_are_reported_with_their_path() {
+        let err =
+            load_server_config(&tls_config("/no/such/cert.pem", "/no/such/key.pem")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("/no/such/cert.pem"));
+    }
+
+    #[test]
+    fn files_without_pem_blocks_are_invalid() {
+        let path = std::env::temp_dir().join("conduit-tls-empty.pem");
+        std::fs::write(&path, "not a pem file\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        let err = load_server_config(&tls_config(path, path)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("no certificate found"));
+    }
+}
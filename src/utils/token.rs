@@ -1,3 +1,5 @@
+use crate::config::TokenConfig;
+use chrono::{DateTime, Utc};
 use jsonwebtoken::{errors::Error, DecodingKey, EncodingKey, Header, TokenData, Validation};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -5,25 +7,30 @@ use uuid::Uuid;
 static KEY: [u8; 16] = *include_bytes!("../../secret.key");
 static ONE_DAY: i64 = 60 * 60 * 24; // in seconds
 
-pub fn decode(token: &str) -> jsonwebtoken::errors::Result<TokenData<Claims>> {
-    jsonwebtoken::decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(&KEY),
-        &Validation::default(),
-    )
+// Tokens without the configured `iss` and `aud` claims are rejected.
+pub fn decode(
+    token: &str,
+    config: &TokenConfig,
+) -> jsonwebtoken::errors::Result<TokenData<Claims>> {
+    let mut validation = Validation {
+        iss: Some(config.issuer.clone()),
+        ..Validation::default()
+    };
+    validation.set_audience(&[&config.audience]);
+    jsonwebtoken::decode::<Claims>(token, &DecodingKey::from_secret(&KEY), &validation)
 }
 
-pub fn generate(user_id: Uuid, now: i64) -> Result<String, Error> {
-    let claims = Claims::new(user_id, now);
+pub fn generate(user_id: Uuid, now: DateTime<Utc>, config: &TokenConfig) -> Result<String, Error> {
+    let claims = Claims::new(user_id, now, config);
     jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(&KEY))
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
-    // aud: String, // Optional. Audience
+    aud: String, // Audience, validated against `JWT_AUDIENCE`
     exp: i64, // Required (validate_exp defaults to true in validation). Expiration time (as UTC timestamp)
     iat: i64, // Optional. Issued at (as UTC timestamp)
-    // iss: String, // Optional. Issuer
+    iss: String, // Issuer, validated against `JWT_ISSUER`
     // nbf: usize, // Optional. Not Before (as UTC timestamp)
     // sub: String, // Optional. Subject (whom token refers to)
     // ---
@@ -31,11 +38,61 @@ pub struct Claims {
 }
 
 impl Claims {
-    pub fn new(user_id: Uuid, now: i64) -> Self {
+    pub fn new(user_id: Uuid, now: DateTime<Utc>, config: &TokenConfig) -> Self {
         Claims {
-            iat: now,
-            exp: now + ONE_DAY,
+            aud: config.audience.clone(),
+            iat: now.timestamp(),
+            exp: now.timestamp() + ONE_DAY,
+            iss: config.issuer.clone(),
             user_id,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::errors::ErrorKind;
+
+    fn config(issuer: &str, audience: &str) -> TokenConfig {
+        TokenConfig {
+            issuer: issuer.to_owned(),
+            audience: audience.to_owned(),
+        }
+    }
+
+    #[test]
+    fn decodes_own_token() {
+        let user_id = Uuid::new_v4();
+        let config = config("conduit", "conduit");
+        let token = generate(user_id, Utc::now(), &config).unwrap();
+
+        assert_eq!(decode(&token, &config).unwrap().claims.user_id, user_id);
+    }
+
+    #[test]
+    fn rejects_wrong_audience() {
+        let token = generate(
+            Uuid::new_v4(),
+            Utc::now(),
+            &config("conduit", "other-service"),
+        )
+        .unwrap();
+
+        let err = decode(&token, &config("conduit", "conduit")).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidAudience));
+    }
+
+    #[test]
+    fn rejects_wrong_issuer() {
+        let token = generate(
+            Uuid::new_v4(),
+            Utc::now(),
+            &config("other-service", "conduit"),
+        )
+        .unwrap();
+
+        let err = decode(&token, &config("conduit", "conduit")).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidIssuer));
+    }
+}
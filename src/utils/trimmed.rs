@@ -0,0 +1,58 @@
+use serde::{Deserialize, Deserializer};
+
+// Drops leading and trailing whitespace from user-supplied identifiers and titles while
+// deserializing, so " jake " is validated, stored and looked up as "jake".
+// Use with `#[serde(deserialize_with = "trimmed::deserialize")]`.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    String::deserialize(deserializer).map(trim)
+}
+
+// Same for optional fields, with `#[serde(default, deserialize_with = "trimmed::deserialize_option")]`.
+pub fn deserialize_option<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer).map(|value| value.map(trim))
+}
+
+fn trim(value: String) -> String {
+    // Most values have nothing to trim, so they are kept without reallocating.
+    if value.trim().len() == value.len() {
+        value
+    } else {
+        value.trim().to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Deserialize)]
+    struct Item {
+        #[serde(deserialize_with = "deserialize")]
+        name: String,
+        #[serde(default, deserialize_with = "deserialize_option")]
+        email: Option<String>,
+    }
+
+    #[test]
+    fn surrounding_whitespace_is_trimmed() {
+        let item: Item =
+            serde_json::from_value(json!({ "name": " jake\t", "email": "\n jake@jake.jake " }))
+                .unwrap();
+        assert_eq!(item.name, "jake");
+        assert_eq!(item.email.as_deref(), Some("jake@jake.jake"));
+    }
+
+    #[test]
+    fn inner_whitespace_is_kept() {
+        let item: Item = serde_json::from_value(json!({ "name": "jake  the dog" })).unwrap();
+        assert_eq!(item.name, "jake  the dog");
+        assert_eq!(item.email, None);
+    }
+}
@@ -0,0 +1,65 @@
+use crate::error::AppError;
+use crate::middleware::error::ErrorResponse;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+static EMAIL_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[^@\s]+@[^@\s]+\.[^@\s]+$").expect("invalid email regex"));
+
+pub fn is_email(text: &str) -> bool {
+    EMAIL_REGEX.is_match(text)
+}
+
+// Collects messages per field and renders them as `{"errors":{"<field>":["<message>"]}}`.
+#[derive(Debug, Default)]
+pub struct ValidationErrors(BTreeMap<&'static str, Vec<String>>);
+
+impl ValidationErrors {
+    pub fn add(&mut self, field: &'static str, message: &str) {
+        self.0.entry(field).or_default().push(message.to_owned());
+    }
+
+    pub fn into_result(self) -> Result<(), AppError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            let errors = self
+                .0
+                .into_iter()
+                .map(|(field, messages)| (field.to_owned(), messages))
+                .collect();
+            Err(AppError::UnprocessableEntity(json!(ErrorResponse {
+                errors
+            })))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_email_test() {
+        assert!(is_email("jake@jake.jake"));
+        assert!(!is_email("notanemail"));
+        assert!(!is_email("jake@jake"));
+        assert!(!is_email("ja ke@jake.jake"));
+    }
+
+    #[test]
+    fn into_result_test() {
+        assert!(ValidationErrors::default().into_result().is_ok());
+
+        let mut errors = ValidationErrors::default();
+        errors.add("email", "is invalid");
+        match errors.into_result() {
+            Err(AppError::UnprocessableEntity(body)) => {
+                assert_eq!(body, json!({"errors": {"email": ["is invalid"]}}))
+            }
+            _ => panic!("expected unprocessable entity"),
+        }
+    }
+}
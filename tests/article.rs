@@ -0,0 +1,851 @@
+mod common;
+
+use actix_web::http::{header, StatusCode};
+use actix_web::test;
+use conduit::config::PaginationConfig;
+use serde_json::{json, Value as JsonValue};
+use std::sync::atomic::Ordering;
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn batch_keeps_requested_order_and_skips_unknown_slugs() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+    let first = common::create_article(&app, &user.token, "first batch article").await;
+    let second = common::create_article(&app, &user.token, "second batch article").await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/articles/batch")
+        .insert_header(common::auth_header(&user.token))
+        .set_json(json!({ "slugs": [second, "no-such-batch-article", first] }))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(common::slugs(&res), vec![second, first]);
+    assert_eq!(res["articlesCount"], 2);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn batch_rejects_too_many_slugs() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+    let slugs = (0..51).map(|i| format!("slug-{}", i)).collect::<Vec<_>>();
+
+    let req = test::TestRequest::post()
+        .uri("/api/articles/batch")
+        .insert_header(common::auth_header(&user.token))
+        .set_json(json!({ "slugs": slugs }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn comments_count_matches_created_comments() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+    let slug = common::create_article(&app, &user.token, "comments count article").await;
+    for body in ["first comment", "second comment"] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/articles/{}/comments", slug))
+            .insert_header(common::auth_header(&user.token))
+            .set_json(json!({ "comment": { "body": body } }))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert!(res.status().is_success());
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/articles/{}", slug))
+        .insert_header(common::auth_header(&user.token))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(res["article"]["commentsCount"], 2);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/articles?author={}", user.username))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(res["articles"][0]["commentsCount"], 2);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn update_by_slug() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+    let slug = common::create_article(&app, &user.token, "update by slug article").await;
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/articles/{}", slug))
+        .insert_header(common::auth_header(&user.token))
+        .set_json(json!({ "article": { "body": "updated body" } }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(body["article"]["slug"], slug.as_str());
+    assert_eq!(body["article"]["body"], "updated body");
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn delete_by_slug() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+    let slug = common::create_article(&app, &user.token, "delete by slug article").await;
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/articles/{}", slug))
+        .insert_header(common::auth_header(&user.token))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/articles/{}", slug))
+        .insert_header(common::auth_header(&user.token))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn update_and_delete_by_someone_else_are_forbidden() {
+    let app = common::init_app(common::test_state()).await;
+    let author = common::create_user(&app).await;
+    let other = common::create_user(&app).await;
+    let slug = common::create_article(&app, &author.token, "forbidden by slug article").await;
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/articles/{}", slug))
+        .insert_header(common::auth_header(&other.token))
+        .set_json(json!({ "article": { "body": "hijacked" } }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(
+        body,
+        json!({"errors": {"body": ["only the author can modify the article"]}})
+    );
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/articles/{}", slug))
+        .insert_header(common::auth_header(&other.token))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn delete_unknown_slug_is_not_found() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+
+    let req = test::TestRequest::delete()
+        .uri("/api/articles/no-such-article-to-delete")
+        .insert_header(common::auth_header(&user.token))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn too_large_limit_is_clamped_to_max_limit() {
+    let mut state = common::test_state();
+    state.config.pagination = PaginationConfig {
+        default_limit: 2,
+        max_limit: 3,
+    };
+    let app = common::init_app(state).await;
+    let user = common::create_user(&app).await;
+    for title in [
+        "first clamp article",
+        "second clamp article",
+        "third clamp article",
+        "fourth clamp article",
+    ] {
+        common::create_article(&app, &user.token, title).await;
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/articles?author={}&limit=500", user.username))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.headers().get("x-limit").unwrap(), "3");
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(common::slugs(&body).len(), 3);
+    assert_eq!(body["articlesCount"], 4);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn negative_limit_is_bad_request() {
+    let app = common::init_app(common::test_state()).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/articles?limit=-1")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn show_is_not_modified_until_the_article_changes() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+    let slug = common::create_article(&app, &user.token, "etag article").await;
+    let uri = format!("/api/articles/{}", slug);
+
+    let req = test::TestRequest::get()
+        .uri(&uri)
+        .insert_header(common::auth_header(&user.token))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let etag = res.headers().get(header::ETAG).unwrap().clone();
+
+    let req = test::TestRequest::get()
+        .uri(&uri)
+        .insert_header(common::auth_header(&user.token))
+        .insert_header((header::IF_NONE_MATCH, etag.clone()))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+
+    let req = test::TestRequest::put()
+        .uri(&uri)
+        .insert_header(common::auth_header(&user.token))
+        .set_json(json!({ "article": { "tagList": ["etag"] } }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get()
+        .uri(&uri)
+        .insert_header(common::auth_header(&user.token))
+        .insert_header((header::IF_NONE_MATCH, etag.clone()))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_ne!(res.headers().get(header::ETAG).unwrap(), &etag);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn favorite_unknown_slug_is_not_found() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/articles/no-such-favorite-article/favorite")
+        .insert_header(common::auth_header(&user.token))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn comment_on_unknown_slug_is_not_found() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/articles/no-such-commented-article/comments")
+        .insert_header(common::auth_header(&user.token))
+        .set_json(json!({ "comment": { "body": "lost comment" } }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn comment_on_someone_elses_article() {
+    let app = common::init_app(common::test_state()).await;
+    let author = common::create_user(&app).await;
+    let reader = common::create_user(&app).await;
+    let slug = common::create_article(&app, &author.token, "commented by reader article").await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/articles/{}/comments", slug))
+        .insert_header(common::auth_header(&reader.token))
+        .set_json(json!({ "comment": { "body": "nice article" } }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn index_by_tag() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+    let tag = format!("tag-{}", user.username);
+    let req = test::TestRequest::post()
+        .uri("/api/articles")
+        .insert_header(common::auth_header(&user.token))
+        .set_json(json!({
+            "article": {
+                "title": "tagged feed article",
+                "description": "description",
+                "body": "body",
+                "tagList": [tag]
+            }
+        }))
+        .to_request();
+    let created: JsonValue = test::call_and_read_body_json(&app, req).await;
+    common::create_article(&app, &user.token, "untagged feed article").await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/articles/tag/{}", tag))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(
+        common::slugs(&res),
+        vec![created["article"]["slug"].as_str().unwrap()]
+    );
+    assert_eq!(res["articlesCount"], 1);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn index_by_unknown_tag_is_empty() {
+    let app = common::init_app(common::test_state()).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/articles/tag/no-such-tag")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(body, json!({ "articles": [], "articlesCount": 0 }));
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn list_is_resolved_for_the_viewer() {
+    let app = common::init_app(common::test_state()).await;
+    let author = common::create_user(&app).await;
+    let reader = common::create_user(&app).await;
+    let slug = common::create_article(&app, &author.token, "viewer list article").await;
+    common::follow(&app, &reader.token, &author.username).await;
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/articles/{}/favorite", slug))
+        .insert_header(common::auth_header(&reader.token))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/articles?author={}", author.username))
+        .insert_header(common::auth_header(&reader.token))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(res["articles"][0]["slug"], slug.as_str());
+    assert_eq!(res["articles"][0]["favorited"], true);
+    assert_eq!(res["articles"][0]["author"]["following"], true);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn lists_by_author_and_tag_are_resolved_for_the_viewer() {
+    let app = common::init_app(common::test_state()).await;
+    let author = common::create_user(&app).await;
+    let reader = common::create_user(&app).await;
+    let tag = format!("viewer-{}", author.username);
+    let req = test::TestRequest::post()
+        .uri("/api/articles")
+        .insert_header(common::auth_header(&author.token))
+        .set_json(json!({
+            "article": {
+                "title": "viewer tagged article",
+                "description": "description",
+                "body": "body",
+                "tagList": [tag],
+            }
+        }))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+    let slug = res["article"]["slug"].as_str().unwrap().to_owned();
+    common::follow(&app, &reader.token, &author.username).await;
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/articles/{}/favorite", slug))
+        .insert_header(common::auth_header(&reader.token))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    for uri in [
+        format!("/api/profiles/{}/articles", author.username),
+        format!("/api/articles/tag/{}", tag),
+    ] {
+        let req = test::TestRequest::get()
+            .uri(&uri)
+            .insert_header(common::auth_header(&reader.token))
+            .to_request();
+        let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(res["articles"][0]["slug"], slug.as_str());
+        assert_eq!(res["articles"][0]["favorited"], true);
+        assert_eq!(res["articles"][0]["author"]["following"], true);
+    }
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn show_counts_views() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+    let slug = common::create_article(&app, &user.token, "viewed article").await;
+
+    for expected in [1, 2] {
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/articles/{}", slug))
+            .insert_header(common::auth_header(&user.token))
+            .to_request();
+        let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["article"]["viewCount"], expected);
+    }
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn validate_previews_slug_without_creating() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/articles/validate")
+        .insert_header(common::auth_header(&user.token))
+        .set_json(json!({
+            "article": {
+                "title": "How to train your dragon",
+                "description": "description",
+                "body": "body",
+                "tagList": ["dragons"]
+            }
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(body, json!({ "slug": "how-to-train-your-dragon" }));
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/articles?author={}", user.username))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(res["articlesCount"], 0);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn validate_rejects_invalid_draft() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+    let tags = (0..11).map(|i| format!("tag-{}", i)).collect::<Vec<_>>();
+
+    let req = test::TestRequest::post()
+        .uri("/api/articles/validate")
+        .insert_header(common::auth_header(&user.token))
+        .set_json(json!({
+            "article": { "title": "", "description": "description", "body": "body", "tagList": tags }
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(
+        body,
+        json!({ "errors": {
+            "tagList": ["is too long (maximum is 10 tags)"],
+            "title": ["can't be blank"]
+        }})
+    );
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn comment_on_deleted_article_is_not_found() {
+    for soft_delete in [false, true] {
+        let mut state = common::test_state();
+        state.config.soft_delete = soft_delete;
+        let app = common::init_app(state).await;
+        let user = common::create_user(&app).await;
+        let slug = common::create_article(&app, &user.token, "deleted commented article").await;
+
+        let req = test::TestRequest::delete()
+            .uri(&format!("/api/articles/{}", slug))
+            .insert_header(common::auth_header(&user.token))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/articles/{}/comments", slug))
+            .insert_header(common::auth_header(&user.token))
+            .set_json(json!({ "comment": { "body": "too late" } }))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(
+            res.status(),
+            StatusCode::NOT_FOUND,
+            "soft_delete={}",
+            soft_delete
+        );
+    }
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn create_rejects_too_long_body() {
+    let mut state = common::test_state();
+    state.config.max_article_body_length = 16;
+    let app = common::init_app(state).await;
+    let user = common::create_user(&app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/articles")
+        .insert_header(common::auth_header(&user.token))
+        .set_json(json!({
+            "article": {
+                "title": "too long article",
+                "description": "description",
+                "body": "x".repeat(17)
+            }
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(
+        body,
+        json!({ "errors": { "body": ["is too long (maximum is 16 bytes)"] } })
+    );
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn create_with_same_idempotency_key_creates_one_article() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+
+    let mut responses = vec![];
+    for _ in 0..2 {
+        let req = test::TestRequest::post()
+            .uri("/api/articles")
+            .insert_header(common::auth_header(&user.token))
+            .insert_header(("Idempotency-Key", "publish-once"))
+            .set_json(json!({
+                "article": {
+                    "title": "idempotent article",
+                    "description": "description",
+                    "body": "body"
+                }
+            }))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let body: JsonValue = test::read_body_json(res).await;
+        responses.push(body);
+    }
+    assert_eq!(responses[0], responses[1]);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/profiles/{}/articles", user.username))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(res["articlesCount"], 1);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn create_reusing_idempotency_key_for_another_article_is_unprocessable() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+
+    let mut statuses = vec![];
+    for title in ["first keyed article", "second keyed article"] {
+        let req = test::TestRequest::post()
+            .uri("/api/articles")
+            .insert_header(common::auth_header(&user.token))
+            .insert_header(("Idempotency-Key", "publish-reused"))
+            .set_json(json!({
+                "article": {
+                    "title": title,
+                    "description": "description",
+                    "body": "body"
+                }
+            }))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        statuses.push(res.status());
+        if res.status() == StatusCode::UNPROCESSABLE_ENTITY {
+            let body: JsonValue = test::read_body_json(res).await;
+            assert_eq!(
+                body,
+                json!({"errors": {"body": ["idempotency key was already used with a different request"]}})
+            );
+        }
+    }
+
+    assert_eq!(
+        statuses,
+        vec![StatusCode::CREATED, StatusCode::UNPROCESSABLE_ENTITY]
+    );
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn create_past_user_write_limit_is_too_many_requests() {
+    let mut state = common::test_state();
+    state.config.user_writes_per_minute = 2;
+    let app = common::init_app(state).await;
+    let user = common::create_user(&app).await;
+    let other = common::create_user(&app).await;
+    common::create_article(&app, &user.token, "first limited article").await;
+    common::create_article(&app, &user.token, "second limited article").await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/articles")
+        .insert_header(common::auth_header(&user.token))
+        .set_json(json!({
+            "article": {
+                "title": "third limited article",
+                "description": "description",
+                "body": "body"
+            }
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(res.headers().get(header::RETRY_AFTER).unwrap(), "30");
+
+    common::create_article(&app, &other.token, "other limited article").await;
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn read_only_rejects_create_but_still_lists() {
+    let state = common::test_state();
+    let app = common::init_app(state.clone()).await;
+    let user = common::create_user(&app).await;
+    state.read_only.store(true, Ordering::SeqCst);
+
+    let req = test::TestRequest::post()
+        .uri("/api/articles")
+        .insert_header(common::auth_header(&user.token))
+        .set_json(json!({
+            "article": {
+                "title": "read-only article",
+                "description": "description",
+                "body": "body"
+            }
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(
+        body,
+        json!({"errors": {"body": ["service is in read-only mode"]}})
+    );
+
+    let req = test::TestRequest::get().uri("/api/articles").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn read_only_shows_article_without_counting_view() {
+    let state = common::test_state();
+    assert!(state.config.track_views);
+    let app = common::init_app(state.clone()).await;
+    let user = common::create_user(&app).await;
+    let slug = common::create_article(&app, &user.token, "read-only viewed article").await;
+    state.read_only.store(true, Ordering::SeqCst);
+
+    for _ in 0..2 {
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/articles/{}", slug))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: JsonValue = test::read_body_json(res).await;
+        assert_eq!(body["article"]["viewCount"], 0);
+    }
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn admin_toggles_read_only() {
+    let mut state = common::test_state();
+    state.config.admin_secret = Some("s3cret".to_owned());
+    let app = common::init_app(state.clone()).await;
+
+    for read_only in [true, false] {
+        let req = test::TestRequest::put()
+            .uri("/api/admin/read-only")
+            .insert_header(("X-Admin-Secret", "s3cret"))
+            .set_json(json!({ "readOnly": read_only }))
+            .to_request();
+        let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res, json!({ "readOnly": read_only }));
+        assert_eq!(state.read_only.load(Ordering::SeqCst), read_only);
+    }
+
+    let req = test::TestRequest::put()
+        .uri("/api/admin/read-only")
+        .insert_header(("X-Admin-Secret", "wrong"))
+        .set_json(json!({ "readOnly": true }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert!(!state.read_only.load(Ordering::SeqCst));
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn bulk_delete_reports_unowned_and_missing_slugs() {
+    let app = common::init_app(common::test_state()).await;
+    let owner = common::create_user(&app).await;
+    let other = common::create_user(&app).await;
+    let first = common::create_article(&app, &owner.token, "first bulk delete article").await;
+    let second = common::create_article(&app, &owner.token, "second bulk delete article").await;
+    let unowned = common::create_article(&app, &other.token, "unowned bulk delete article").await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/articles/bulk-delete")
+        .insert_header(common::auth_header(&owner.token))
+        .set_json(json!({
+            "slugs": [first, unowned, "no-such-bulk-delete-article", second]
+        }))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(
+        res,
+        json!({
+            "deleted": [first, second],
+            "forbidden": [unowned],
+            "notFound": ["no-such-bulk-delete-article"],
+        })
+    );
+    for (slug, status) in [
+        (&first, StatusCode::NOT_FOUND),
+        (&second, StatusCode::NOT_FOUND),
+        (&unowned, StatusCode::OK),
+    ] {
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/articles/{}", slug))
+            .insert_header(common::auth_header(&owner.token))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), status);
+    }
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn bulk_delete_requires_token() {
+    let app = common::init_app(common::test_state()).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/articles/bulk-delete")
+        .set_json(json!({ "slugs": ["any-slug"] }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn create_is_created_with_location() {
+    let mut state = common::test_state();
+    state.config.public_base_url = Some("https://api.example.com".to_owned());
+    let app = common::init_app(state).await;
+    let user = common::create_user(&app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/articles")
+        .insert_header(common::auth_header(&user.token))
+        .set_json(json!({
+            "article": {
+                "title": "located article",
+                "description": "description",
+                "body": "body"
+            }
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let location = res.headers().get(header::LOCATION).unwrap().clone();
+    let body: JsonValue = test::read_body_json(res).await;
+    let slug = body["article"]["slug"].as_str().unwrap();
+    assert_eq!(
+        location,
+        format!("https://api.example.com/api/articles/{}", slug).as_str()
+    );
+
+    let req = test::TestRequest::get()
+        .uri(
+            location
+                .to_str()
+                .unwrap()
+                .trim_start_matches("https://api.example.com"),
+        )
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(res["article"]["slug"], slug);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn create_location_is_relative_without_public_base_url() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/articles")
+        .insert_header(common::auth_header(&user.token))
+        .set_json(json!({
+            "article": {
+                "title": "relative located article",
+                "description": "description",
+                "body": "body"
+            }
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::CREATED);
+    assert_eq!(
+        res.headers().get(header::LOCATION).unwrap(),
+        "/api/articles/relative-located-article"
+    );
+}
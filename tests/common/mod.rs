@@ -0,0 +1,223 @@
+#![allow(dead_code)]
+
+use actix_http::Request;
+use actix_web::body::MessageBody;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::middleware::{Compress, Condition};
+use actix_web::{test, web, App};
+use chrono::{Duration, Utc};
+use conduit::config::Config;
+use conduit::constants::{self, env_key};
+use conduit::middleware::{self, state::AppState};
+use conduit::routes;
+use conduit::schema::articles;
+use conduit::utils::hasher;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::r2d2::{self, ConnectionManager, CustomizeConnection, Pool};
+use dotenv::dotenv;
+use serde_json::{json, Value as JsonValue};
+use std::env;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+// Every test gets its own pool holding a single connection, and that connection runs inside a
+// transaction which is never committed. Everything a test writes is rolled back when its pool
+// is dropped, so the database stays clean and tests stay isolated from each other.
+#[derive(Debug)]
+struct TestTransaction;
+
+impl CustomizeConnection<PgConnection, r2d2::Error> for TestTransaction {
+    fn on_acquire(&self, conn: &mut PgConnection) -> Result<(), r2d2::Error> {
+        conn.begin_test_transaction()
+            .map_err(r2d2::Error::QueryError)
+    }
+}
+
+pub fn test_state() -> AppState {
+    dotenv().ok();
+    let database_url = env::var(env_key::TEST_DATABASE_URL).expect("TEST_DATABASE_URL must be set");
+    let manager = ConnectionManager::<PgConnection>::new(database_url);
+    let pool = Pool::builder()
+        .max_size(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connection_customizer(Box::new(TestTransaction))
+        .build(manager)
+        .expect("Failed to create test pool");
+    AppState {
+        pool,
+        // Reads must see the test transaction, so there is no replica.
+        replica_pool: None,
+        // The cheapest cost keeps signups fast.
+        config: Config {
+            bcrypt_cost: hasher::MIN_COST,
+            ..Config::default()
+        },
+        metrics: middleware::metrics::Metrics::new(),
+        rate_limiter: middleware::rate_limit::RateLimiter::new(),
+        read_only: Arc::new(AtomicBool::new(false)),
+    }
+}
+
+// Unlike `test_state`, writes made through this connection are committed, so callers clean up.
+pub fn establish_committing_connection() -> PgConnection {
+    dotenv().ok();
+    let database_url = env::var(env_key::TEST_DATABASE_URL).expect("TEST_DATABASE_URL must be set");
+    PgConnection::establish(&database_url).expect("Failed to connect test database")
+}
+
+// Keep the wiring in sync with `main`.
+pub async fn init_app(
+    state: AppState,
+) -> impl Service<Request, Response = ServiceResponse<impl MessageBody>, Error = actix_web::Error> {
+    test::init_service(
+        App::new()
+            .wrap(middleware::localize::Localize)
+            .wrap(Condition::new(
+                state.config.compression_enabled,
+                middleware::compress::SkipSmallBodies,
+            ))
+            .wrap(Condition::new(
+                state.config.compression_enabled,
+                Compress::default(),
+            ))
+            .app_data(web::Data::new(state.clone()))
+            .app_data(middleware::json::json_config(
+                state.config.json_payload_limit,
+            ))
+            .wrap(middleware::cors::cors())
+            .wrap(middleware::auth::Authentication)
+            .wrap(middleware::read_only::ReadOnly)
+            .wrap(middleware::catch_panic::CatchPanic)
+            .wrap(middleware::metrics::RequestMetrics)
+            .wrap(middleware::request_id::RequestId)
+            .configure(routes::api),
+    )
+    .await
+}
+
+pub struct TestUser {
+    pub email: String,
+    pub username: String,
+    pub password: String,
+    pub token: String,
+}
+
+// Usernames and emails are random so that concurrent tests never contend on unique keys.
+pub async fn create_user<S, B>(app: &S) -> TestUser
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let username = format!("user-{}", Uuid::new_v4().to_simple());
+    let email = format!("{}@example.com", username);
+    let password = "password".to_owned();
+    let req = test::TestRequest::post()
+        .uri("/api/users")
+        .set_json(json!({
+            "user": { "username": username, "email": email, "password": password }
+        }))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(app, req).await;
+    let token = res["user"]["token"]
+        .as_str()
+        .expect("signup response has no token")
+        .to_owned();
+    TestUser {
+        email,
+        username,
+        password,
+        token,
+    }
+}
+
+pub async fn fetch_token<S, B>(app: &S, email: &str, password: &str) -> String
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let req = test::TestRequest::post()
+        .uri("/api/users/login")
+        .set_json(json!({ "user": { "email": email, "password": password } }))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(app, req).await;
+    res["user"]["token"]
+        .as_str()
+        .expect("login response has no token")
+        .to_owned()
+}
+
+// Returns the response body of `PUT /api/user` with `user` as the payload.
+pub async fn update_user<S, B>(app: &S, token: &str, user: JsonValue) -> JsonValue
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let req = test::TestRequest::put()
+        .uri("/api/user")
+        .insert_header(auth_header(token))
+        .set_json(json!({ "user": user }))
+        .to_request();
+    test::call_and_read_body_json(app, req).await
+}
+
+pub fn auth_header(token: &str) -> (&'static str, String) {
+    (constants::AUTHORIZATION, format!("Token {}", token))
+}
+
+// Returns the slug of the created article.
+pub async fn create_article<S, B>(app: &S, token: &str, title: &str) -> String
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let req = test::TestRequest::post()
+        .uri("/api/articles")
+        .insert_header(auth_header(token))
+        .set_json(json!({
+            "article": { "title": title, "description": "description", "body": "body" }
+        }))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(app, req).await;
+    res["article"]["slug"]
+        .as_str()
+        .expect("create article response has no slug")
+        .to_owned()
+}
+
+pub async fn follow<S, B>(app: &S, token: &str, username: &str)
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/profiles/{}/follow", username))
+        .insert_header(auth_header(token))
+        .to_request();
+    let res = test::call_service(app, req).await;
+    assert!(res.status().is_success(), "failed to follow {}", username);
+}
+
+// Everything a test writes shares one transaction, and so one `now()`. This spreads the
+// articles a minute apart, oldest first, so that ordering by creation time is observable.
+pub fn stagger_created_at(state: &AppState, slugs: &[String]) {
+    let conn = state.pool.get().expect("Failed to get test connection");
+    for (i, slug) in slugs.iter().enumerate() {
+        let created_at = Utc::now() - Duration::minutes((slugs.len() - i) as i64);
+        diesel::update(articles::table.filter(articles::slug.eq(slug)))
+            .set(articles::created_at.eq(created_at))
+            .execute(&conn)
+            .expect("Failed to update created_at");
+    }
+}
+
+pub fn slugs(res: &JsonValue) -> Vec<String> {
+    res["articles"]
+        .as_array()
+        .expect("response has no articles")
+        .iter()
+        .map(|article| article["slug"].as_str().unwrap().to_owned())
+        .collect()
+}
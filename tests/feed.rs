@@ -0,0 +1,218 @@
+mod common;
+
+use actix_http::Request;
+use actix_web::body::MessageBody;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::http::StatusCode;
+use actix_web::test;
+use serde_json::Value as JsonValue;
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn feed_pages_with_before_cursor_neither_skip_nor_duplicate() {
+    let app = common::init_app(common::test_state()).await;
+    let reader = common::create_user(&app).await;
+    let author = common::create_user(&app).await;
+    common::follow(&app, &reader.token, &author.username).await;
+    for title in [
+        "first cursor article",
+        "second cursor article",
+        "third cursor article",
+    ] {
+        common::create_article(&app, &author.token, title).await;
+    }
+
+    let (first_page, cursor) = fetch_feed_page(&app, &reader.token, "?limit=2").await;
+    let first_slugs = common::slugs(&first_page);
+    assert_eq!(first_slugs.len(), 2);
+
+    let query = format!(
+        "?limit=2&before={}",
+        cursor.expect("a full page has a next cursor")
+    );
+    let (second_page, cursor) = fetch_feed_page(&app, &reader.token, &query).await;
+    let second_slugs = common::slugs(&second_page);
+    assert_eq!(second_slugs.len(), 1);
+    assert!(!first_slugs.contains(&second_slugs[0]));
+    assert_eq!(second_page["articlesCount"], 3);
+    assert_eq!(cursor, None);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn feed_cursor_outlives_its_article() {
+    let state = common::test_state();
+    let app = common::init_app(state.clone()).await;
+    let reader = common::create_user(&app).await;
+    let author = common::create_user(&app).await;
+    common::follow(&app, &reader.token, &author.username).await;
+    let oldest_first = vec![
+        common::create_article(&app, &author.token, "older outliving article").await,
+        common::create_article(&app, &author.token, "newer outliving article").await,
+    ];
+    common::stagger_created_at(&state, &oldest_first);
+
+    let (_, cursor) = fetch_feed_page(&app, &reader.token, "?limit=1").await;
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/articles/{}", oldest_first[1]))
+        .insert_header(common::auth_header(&author.token))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+
+    let query = format!("?limit=1&before={}", cursor.unwrap());
+    let (second_page, _) = fetch_feed_page(&app, &reader.token, &query).await;
+    assert_eq!(common::slugs(&second_page), vec![oldest_first[0].clone()]);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn feed_rejects_invalid_cursor() {
+    let app = common::init_app(common::test_state()).await;
+    let reader = common::create_user(&app).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/articles/feed?before=some-slug")
+        .insert_header(common::auth_header(&reader.token))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(body["errors"]["body"][0], "invalid feed cursor");
+}
+
+async fn fetch_feed<S, B>(app: &S, token: &str, query: &str) -> JsonValue
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/articles/feed{}", query))
+        .insert_header(common::auth_header(token))
+        .to_request();
+    test::call_and_read_body_json(app, req).await
+}
+
+async fn fetch_feed_page<S, B>(app: &S, token: &str, query: &str) -> (JsonValue, Option<String>)
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/articles/feed{}", query))
+        .insert_header(common::auth_header(token))
+        .to_request();
+    let res = test::call_service(app, req).await;
+    let cursor = res
+        .headers()
+        .get("X-Next-Cursor")
+        .map(|value| value.to_str().unwrap().to_owned());
+    (test::read_body_json(res).await, cursor)
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn feed_lists_only_followed_authors() {
+    let app = common::init_app(common::test_state()).await;
+    let reader = common::create_user(&app).await;
+    let followed = common::create_user(&app).await;
+    let stranger = common::create_user(&app).await;
+    common::follow(&app, &reader.token, &followed.username).await;
+    let followed_slug = common::create_article(&app, &followed.token, "followed article").await;
+    common::create_article(&app, &stranger.token, "stranger article").await;
+    common::create_article(&app, &reader.token, "own article").await;
+
+    let feed = fetch_feed(&app, &reader.token, "").await;
+
+    assert_eq!(common::slugs(&feed), vec![followed_slug]);
+    assert_eq!(feed["articlesCount"], 1);
+    assert_eq!(feed["articles"][0]["author"]["following"], true);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn feed_is_newest_first() {
+    let state = common::test_state();
+    let app = common::init_app(state.clone()).await;
+    let reader = common::create_user(&app).await;
+    let first_author = common::create_user(&app).await;
+    let second_author = common::create_user(&app).await;
+    common::follow(&app, &reader.token, &first_author.username).await;
+    common::follow(&app, &reader.token, &second_author.username).await;
+    let oldest_first = vec![
+        common::create_article(&app, &first_author.token, "oldest feed article").await,
+        common::create_article(&app, &second_author.token, "middle feed article").await,
+        common::create_article(&app, &first_author.token, "newest feed article").await,
+    ];
+    common::stagger_created_at(&state, &oldest_first);
+
+    let feed = fetch_feed(&app, &reader.token, "").await;
+
+    let newest_first = oldest_first.into_iter().rev().collect::<Vec<_>>();
+    assert_eq!(common::slugs(&feed), newest_first);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn feed_pages_with_offset_neither_skip_nor_duplicate() {
+    let state = common::test_state();
+    let app = common::init_app(state.clone()).await;
+    let reader = common::create_user(&app).await;
+    let author = common::create_user(&app).await;
+    common::follow(&app, &reader.token, &author.username).await;
+    let mut created = vec![];
+    for i in 0..5 {
+        let title = format!("offset feed article {}", i);
+        created.push(common::create_article(&app, &author.token, &title).await);
+    }
+    common::stagger_created_at(&state, &created);
+
+    let mut paged = vec![];
+    for offset in [0, 2, 4] {
+        let query = format!("?limit=2&offset={}", offset);
+        let page = fetch_feed(&app, &reader.token, &query).await;
+        assert_eq!(page["articlesCount"], 5);
+        paged.extend(common::slugs(&page));
+    }
+
+    let newest_first = created.into_iter().rev().collect::<Vec<_>>();
+    assert_eq!(paged, newest_first);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn feed_drops_unfollowed_author_from_later_pages() {
+    let state = common::test_state();
+    let app = common::init_app(state.clone()).await;
+    let reader = common::create_user(&app).await;
+    let kept = common::create_user(&app).await;
+    let dropped = common::create_user(&app).await;
+    common::follow(&app, &reader.token, &kept.username).await;
+    common::follow(&app, &reader.token, &dropped.username).await;
+    let oldest_first = vec![
+        common::create_article(&app, &kept.token, "kept feed article one").await,
+        common::create_article(&app, &dropped.token, "dropped feed article one").await,
+        common::create_article(&app, &kept.token, "kept feed article two").await,
+        common::create_article(&app, &dropped.token, "dropped feed article two").await,
+    ];
+    common::stagger_created_at(&state, &oldest_first);
+
+    let (first_page, cursor) = fetch_feed_page(&app, &reader.token, "?limit=2").await;
+    assert_eq!(
+        common::slugs(&first_page),
+        vec![oldest_first[3].clone(), oldest_first[2].clone()]
+    );
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/profiles/{}/follow", dropped.username))
+        .insert_header(common::auth_header(&reader.token))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+
+    let query = format!("?limit=2&before={}", cursor.unwrap());
+    let (second_page, _) = fetch_feed_page(&app, &reader.token, &query).await;
+    assert_eq!(common::slugs(&second_page), vec![oldest_first[0].clone()]);
+    assert_eq!(second_page["articlesCount"], 2);
+}
@@ -0,0 +1,177 @@
+mod common;
+
+use actix_web::{http::StatusCode, test};
+use serde_json::{json, Value as JsonValue};
+
+fn usernames(res: &JsonValue) -> Vec<String> {
+    res["profiles"]
+        .as_array()
+        .expect("response has no profiles")
+        .iter()
+        .map(|profile| profile["username"].as_str().unwrap().to_owned())
+        .collect()
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn followers_and_following() {
+    let app = common::init_app(common::test_state()).await;
+    let author = common::create_user(&app).await;
+    let reader = common::create_user(&app).await;
+    let viewer = common::create_user(&app).await;
+    common::follow(&app, &reader.token, &author.username).await;
+    common::follow(&app, &viewer.token, &reader.username).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/profiles/{}/followers", author.username))
+        .insert_header(common::auth_header(&viewer.token))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(usernames(&res), vec![reader.username.clone()]);
+    assert_eq!(res["profiles"][0]["following"], true);
+    assert_eq!(res["profilesCount"], 1);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/profiles/{}/following", reader.username))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(usernames(&res), vec![author.username.clone()]);
+    assert_eq!(res["profiles"][0]["following"], false);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn followers_of_user_without_followers_is_empty() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/profiles/{}/followers", user.username))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(res, json!({ "profiles": [], "profilesCount": 0 }));
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn followers_of_unknown_user_is_not_found() {
+    let app = common::init_app(common::test_state()).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/profiles/no-such-user/followers")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn follow_many_reports_unknown_usernames() {
+    let app = common::init_app(common::test_state()).await;
+    let reader = common::create_user(&app).await;
+    let first = common::create_user(&app).await;
+    let second = common::create_user(&app).await;
+    common::follow(&app, &reader.token, &second.username).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/profiles/follow")
+        .insert_header(common::auth_header(&reader.token))
+        .set_json(json!({
+            "usernames": [second.username, "no-such-user", first.username, second.username]
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(
+        usernames(&body),
+        vec![second.username.clone(), first.username.clone()]
+    );
+    assert_eq!(body["profiles"][0]["following"], true);
+    assert_eq!(body["profiles"][1]["following"], true);
+    assert_eq!(body["notFound"], json!(["no-such-user"]));
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/profiles/{}/following", reader.username))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(res["profilesCount"], 2);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn follow_many_rejects_too_many_usernames() {
+    let app = common::init_app(common::test_state()).await;
+    let reader = common::create_user(&app).await;
+    let usernames = (0..51).map(|i| format!("user-{}", i)).collect::<Vec<_>>();
+
+    let req = test::TestRequest::post()
+        .uri("/api/profiles/follow")
+        .insert_header(common::auth_header(&reader.token))
+        .set_json(json!({ "usernames": usernames }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn follow_status_is_since_follow_creation() {
+    use chrono::{DateTime, Utc};
+    use conduit::schema::{follows, users};
+    use conduit::utils::date::Iso8601;
+    use diesel::prelude::*;
+
+    let state = common::test_state();
+    let app = common::init_app(state.clone()).await;
+    let author = common::create_user(&app).await;
+    let reader = common::create_user(&app).await;
+    let uri = format!("/api/profiles/{}/follow-status", author.username);
+
+    let req = test::TestRequest::get()
+        .uri(&uri)
+        .insert_header(common::auth_header(&reader.token))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(res, json!({ "following": false, "since": null }));
+
+    common::follow(&app, &reader.token, &author.username).await;
+    let created_at = {
+        let conn = state.pool.get().unwrap();
+        follows::table
+            .inner_join(users::table.on(users::id.eq(follows::follower_id)))
+            .filter(users::username.eq(&reader.username))
+            .select(follows::created_at)
+            .first::<DateTime<Utc>>(&conn)
+            .unwrap()
+    };
+
+    let req = test::TestRequest::get()
+        .uri(&uri)
+        .insert_header(common::auth_header(&reader.token))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(
+        res,
+        json!({ "following": true, "since": Iso8601(created_at) })
+    );
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn follow_status_of_unknown_user_is_not_found() {
+    let app = common::init_app(common::test_state()).await;
+    let reader = common::create_user(&app).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/profiles/no-such-user-here/follow-status")
+        .insert_header(common::auth_header(&reader.token))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,22 @@
+mod common;
+
+use actix_web::{http::StatusCode, test};
+use serde_json::{json, Value as JsonValue};
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn popular_rejects_negative_limit() {
+    let app = common::init_app(common::test_state()).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/tags/popular?limit=-1")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(
+        body,
+        json!({"errors": {"body": ["limit must not be negative"]}})
+    );
+}
@@ -0,0 +1,588 @@
+mod common;
+
+use actix_web::{
+    http::{header, StatusCode},
+    test,
+};
+use chrono::{Duration, Utc};
+use conduit::app::user::model::User;
+use conduit::config::{S3Config, TokenConfig};
+use conduit::error::AppError;
+use conduit::utils::{hasher, token};
+use serde_json::{json, Value as JsonValue};
+use std::sync::{Arc, Barrier};
+use std::thread;
+use uuid::Uuid;
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn signup_then_fetch_current_user() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/user")
+        .insert_header(common::auth_header(&user.token))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(body["user"]["username"], user.username.as_str());
+    assert_eq!(body["user"]["email"], user.email.as_str());
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn login_returns_token() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+
+    let token = common::fetch_token(&app, &user.email, &user.password).await;
+
+    assert!(!token.is_empty());
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn login_by_username_returns_token() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/users/login")
+        .set_json(json!({
+            "user": { "username": user.username, "password": user.password }
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(body["user"]["email"], user.email.as_str());
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn login_by_username_with_wrong_password_is_unauthorized() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/users/login")
+        .set_json(json!({
+            "user": { "username": user.username, "password": "wrong-password" }
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn login_with_both_email_and_username_is_unprocessable() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/users/login")
+        .set_json(json!({
+            "user": {
+                "email": user.email,
+                "username": user.username,
+                "password": user.password
+            }
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn signup_with_malformed_json_is_unprocessable() {
+    let app = common::init_app(common::test_state()).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/users")
+        .insert_header(("content-type", "application/json"))
+        .set_payload(r#"{"user": {"email": "jake@jake.jake",}}"#)
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(
+        body,
+        json!({"errors": {"body": ["invalid JSON: trailing comma at line 1 column 37"]}})
+    );
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn signup_with_non_json_body_is_unsupported_media_type() {
+    let app = common::init_app(common::test_state()).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/users")
+        .insert_header(("content-type", "text/plain"))
+        .set_payload("user[email]=jake@jake.jake")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn current_user_requires_token() {
+    let app = common::init_app(common::test_state()).await;
+
+    let req = test::TestRequest::get().uri("/api/user").to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(
+        body,
+        json!({ "errors": { "body": ["missing authorization"] } })
+    );
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn malformed_authorization_is_bad_request() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+
+    for value in [
+        format!("Bearer {}", user.token),
+        user.token.clone(),
+        "Token".to_owned(),
+        "Token   ".to_owned(),
+    ] {
+        let req = test::TestRequest::get()
+            .uri("/api/user")
+            .insert_header(("Authorization", value.as_str()))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST, "{}", value);
+        let body: JsonValue = test::read_body_json(res).await;
+        assert_eq!(
+            body,
+            json!({ "errors": { "body": ["invalid authorization header"] } })
+        );
+    }
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn invalid_token_is_unauthorized() {
+    let app = common::init_app(common::test_state()).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/user")
+        .insert_header(common::auth_header("not-a-jwt"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(body, json!({ "errors": { "body": ["invalid token"] } }));
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn token_for_missing_user_is_unauthorized() {
+    let app = common::init_app(common::test_state()).await;
+    let token = token::generate(Uuid::new_v4(), Utc::now(), &TokenConfig::default()).unwrap();
+
+    let req = test::TestRequest::get()
+        .uri("/api/user")
+        .insert_header(common::auth_header(&token))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn token_for_another_audience_is_unauthorized() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+    let claims = token::decode(&user.token, &TokenConfig::default())
+        .unwrap()
+        .claims;
+    let token = token::generate(
+        claims.user_id,
+        Utc::now(),
+        &TokenConfig {
+            audience: "other-service".to_owned(),
+            ..TokenConfig::default()
+        },
+    )
+    .unwrap();
+
+    let req = test::TestRequest::get()
+        .uri("/api/user")
+        .insert_header(common::auth_header(&token))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn delete_account() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+
+    let req = test::TestRequest::delete()
+        .uri("/api/user")
+        .insert_header(common::auth_header(&user.token))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+    let req = test::TestRequest::get()
+        .uri("/api/user")
+        .insert_header(common::auth_header(&user.token))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn delete_account_requires_token() {
+    let app = common::init_app(common::test_state()).await;
+
+    let req = test::TestRequest::delete().uri("/api/user").to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn update_bio_only_keeps_other_fields() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+    common::update_user(
+        &app,
+        &user.token,
+        json!({ "image": "https://example.com/a.png" }),
+    )
+    .await;
+
+    let res = common::update_user(&app, &user.token, json!({ "bio": "hello" })).await;
+
+    assert_eq!(res["user"]["bio"], "hello");
+    assert_eq!(res["user"]["email"], user.email.as_str());
+    assert_eq!(res["user"]["username"], user.username.as_str());
+    assert_eq!(res["user"]["image"], "https://example.com/a.png");
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn update_with_blank_username_is_unprocessable() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+
+    let req = test::TestRequest::put()
+        .uri("/api/user")
+        .insert_header(common::auth_header(&user.token))
+        .set_json(json!({ "user": { "username": " " } }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let req = test::TestRequest::get()
+        .uri("/api/user")
+        .insert_header(common::auth_header(&user.token))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(res["user"]["username"], user.username.as_str());
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn update_with_null_clears_bio_only() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+    common::update_user(
+        &app,
+        &user.token,
+        json!({ "bio": "hello", "image": "https://example.com/a.png" }),
+    )
+    .await;
+
+    let res = common::update_user(&app, &user.token, json!({ "bio": null })).await;
+
+    assert_eq!(res["user"]["bio"], JsonValue::Null);
+    assert_eq!(res["user"]["image"], "https://example.com/a.png");
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn upload_image_without_s3_is_not_implemented() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/user/image")
+        .insert_header(common::auth_header(&user.token))
+        .insert_header(("content-type", "multipart/form-data; boundary=BOUNDARY"))
+        .set_payload(
+            "--BOUNDARY\r\n\
+             Content-Disposition: form-data; name=\"image\"; filename=\"a.png\"\r\n\
+             Content-Type: image/png\r\n\r\n\
+             png\r\n\
+             --BOUNDARY--\r\n",
+        )
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::NOT_IMPLEMENTED);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn upload_image_not_matching_its_content_type_is_unsupported() {
+    let mut state = common::test_state();
+    state.config.s3 = Some(S3Config {
+        bucket: "conduit-images".to_owned(),
+        region: "us-east-1".to_owned(),
+        endpoint: "http://127.0.0.1:1".to_owned(),
+        access_key_id: "unused".to_owned(),
+        secret_access_key: "unused".to_owned(),
+        public_url: "http://127.0.0.1:1/conduit-images".to_owned(),
+    });
+    let app = common::init_app(state).await;
+    let user = common::create_user(&app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/user/image")
+        .insert_header(common::auth_header(&user.token))
+        .insert_header(("content-type", "multipart/form-data; boundary=BOUNDARY"))
+        .set_payload(
+            "--BOUNDARY\r\n\
+             Content-Disposition: form-data; name=\"image\"; filename=\"a.png\"\r\n\
+             Content-Type: image/png\r\n\r\n\
+             <svg xmlns=\"http://www.w3.org/2000/svg\"></svg>\r\n\
+             --BOUNDARY--\r\n",
+        )
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(
+        body,
+        json!({"errors": {"body": ["image content doesn't match its content type"]}})
+    );
+}
+
+// The signups have to commit to race each other, so the surviving user is deleted at the end.
+#[test]
+#[ignore = "requires TEST_DATABASE_URL"]
+fn concurrent_duplicate_signups_create_one_user() {
+    let username = format!("user-{}", Uuid::new_v4().to_simple());
+    let email = format!("{}@example.com", username);
+    let barrier = Arc::new(Barrier::new(2));
+
+    let handles: Vec<_> = (0..2)
+        .map(|_| {
+            let (email, username, barrier) = (email.clone(), username.clone(), barrier.clone());
+            thread::spawn(move || {
+                let conn = common::establish_committing_connection();
+                barrier.wait();
+                User::signup(
+                    &conn,
+                    &email,
+                    &username,
+                    "password",
+                    hasher::MIN_COST,
+                    &TokenConfig::default(),
+                )
+            })
+        })
+        .collect();
+    let results: Vec<_> = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect();
+
+    let conn = common::establish_committing_connection();
+    for (user, _) in results.iter().filter_map(|result| result.as_ref().ok()) {
+        User::delete(&conn, user.id).unwrap();
+    }
+    assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+    assert!(results
+        .iter()
+        .any(|result| matches!(result, Err(AppError::UnprocessableEntity(_)))));
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn refresh_issues_a_new_token() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/user/refresh")
+        .insert_header(common::auth_header(&user.token))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(body["user"]["username"], user.username.as_str());
+    let refreshed = body["user"]["token"].as_str().unwrap();
+    let claims = token::decode(refreshed, &TokenConfig::default())
+        .unwrap()
+        .claims;
+    let original = token::decode(&user.token, &TokenConfig::default())
+        .unwrap()
+        .claims;
+    assert_eq!(claims.user_id, original.user_id);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn refresh_rejects_expired_token() {
+    let app = common::init_app(common::test_state()).await;
+    let user = common::create_user(&app).await;
+    let user_id = token::decode(&user.token, &TokenConfig::default())
+        .unwrap()
+        .claims
+        .user_id;
+    let expired = token::generate(
+        user_id,
+        Utc::now() - Duration::days(2),
+        &TokenConfig::default(),
+    )
+    .unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/api/user/refresh")
+        .insert_header(common::auth_header(&expired))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn signup_trims_username_and_email() {
+    let app = common::init_app(common::test_state()).await;
+    let username = format!("user-{}", Uuid::new_v4().to_simple());
+    let email = format!("{}@example.com", username);
+
+    let req = test::TestRequest::post()
+        .uri("/api/users")
+        .set_json(json!({
+            "user": {
+                "username": format!("  {} ", username),
+                "email": format!(" {}\t", email),
+                "password": "password"
+            }
+        }))
+        .to_request();
+    let res: JsonValue = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(res["user"]["username"], username.as_str());
+    assert_eq!(res["user"]["email"], email.as_str());
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/profiles/{}", username))
+        .insert_header(common::auth_header(res["user"]["token"].as_str().unwrap()))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let req = test::TestRequest::post()
+        .uri("/api/users/login")
+        .set_json(json!({
+            "user": { "username": format!(" {} ", username), "password": "password" }
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // Trimmed before the unique constraint, so padding doesn't make a new account.
+    let req = test::TestRequest::post()
+        .uri("/api/users")
+        .set_json(json!({
+            "user": {
+                "username": format!("other-{}", username),
+                "email": format!("  {}  ", email),
+                "password": "password"
+            }
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn signup_errors_follow_accept_language() {
+    let app = common::init_app(common::test_state()).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/users")
+        .insert_header((header::ACCEPT_LANGUAGE, "ja"))
+        .set_json(json!({
+            "user": { "username": "localized-user", "email": "", "password": "" }
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(
+        body,
+        json!({"errors": {
+            "email": ["は不正な値です"],
+            "password": ["は 8 文字以上で入力してください"],
+        }})
+    );
+}
+
+#[actix_web::test]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn signup_follows_signup_enabled() {
+    let state = common::test_state();
+    let enabled_app = common::init_app(state.clone()).await;
+    let user = common::create_user(&enabled_app).await;
+    let mut state = state;
+    state.config.signup_enabled = false;
+    let app = common::init_app(state).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/users")
+        .set_json(json!({
+            "user": {
+                "username": format!("closed-{}", Uuid::new_v4().to_simple()),
+                "email": "closed@example.com",
+                "password": "password"
+            }
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    let body: JsonValue = test::read_body_json(res).await;
+    assert_eq!(
+        body,
+        json!({"errors": {"body": ["registration is disabled"]}})
+    );
+
+    let token = common::fetch_token(&app, &user.email, &user.password).await;
+    assert!(!token.is_empty());
+}